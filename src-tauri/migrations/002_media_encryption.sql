@@ -0,0 +1,17 @@
+-- ============================================================================
+-- MEDIA ENCRYPTION KEYS
+-- ============================================================================
+-- One key per offline session. Downloaded media is encrypted with the key of
+-- the session it was downloaded under; deleting the key makes the files
+-- unreadable.
+
+CREATE TABLE IF NOT EXISTS offline_session_keys (
+                                                  session_id TEXT PRIMARY KEY,
+                                                  media_key TEXT NOT NULL,
+                                                  created_at TEXT NOT NULL,
+                                                  FOREIGN KEY (session_id) REFERENCES offline_sessions(id) ON DELETE CASCADE
+  );
+
+ALTER TABLE media_cache ADD COLUMN encryption_session_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_media_cache_encryption_session ON media_cache(encryption_session_id);
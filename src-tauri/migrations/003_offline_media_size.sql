@@ -0,0 +1,7 @@
+-- ============================================================================
+-- OFFLINE SESSION MEDIA SIZE
+-- ============================================================================
+-- Expected size of the course's media package, so storage reports can compare
+-- what is on disk with what should be there.
+
+ALTER TABLE offline_sessions ADD COLUMN total_media_size_bytes INTEGER;
@@ -0,0 +1,21 @@
+-- ============================================================================
+-- QUIZ ATTEMPT DEADLINES
+-- ============================================================================
+-- deadline_at is fixed when a timed attempt starts (started_at plus the quiz's
+-- time limit). answered_at is stamped locally when an answer is saved, so
+-- answers that arrive after the deadline can be left out of the score.
+
+ALTER TABLE quiz_attempts ADD COLUMN deadline_at TEXT;
+
+ALTER TABLE quiz_answers ADD COLUMN answered_at TEXT;
+
+-- Attempts that were already running get a deadline from their quiz
+UPDATE quiz_attempts
+SET deadline_at = (
+    SELECT strftime('%Y-%m-%dT%H:%M:%SZ', quiz_attempts.started_at, '+' || q.time_limit_minutes || ' minutes')
+    FROM quizzes q
+    WHERE q.id = quiz_attempts.quiz_id AND q.time_limit_minutes > 0
+)
+WHERE status = 'in_progress';
+
+CREATE INDEX IF NOT EXISTS idx_quiz_attempts_deadline ON quiz_attempts(status, deadline_at);
@@ -0,0 +1,21 @@
+-- ============================================================================
+-- ATTEMPT QUESTION ORDER
+-- ============================================================================
+-- Each attempt gets a shuffle seed when it starts. The order it produces is
+-- stored per attempt, so resuming or reviewing shows exactly what the learner
+-- saw even if the quiz is re-downloaded later.
+
+ALTER TABLE quizzes ADD COLUMN shuffle_options BOOLEAN DEFAULT 0;
+
+ALTER TABLE quiz_attempts ADD COLUMN shuffle_seed INTEGER;
+
+CREATE TABLE IF NOT EXISTS attempt_question_order (
+                                                    attempt_id TEXT NOT NULL,
+                                                    question_id TEXT NOT NULL,
+                                                    position INTEGER NOT NULL,
+                                                    option_order TEXT NOT NULL DEFAULT '[]',
+                                                    PRIMARY KEY (attempt_id, question_id),
+  FOREIGN KEY (attempt_id) REFERENCES quiz_attempts(id) ON DELETE CASCADE
+  );
+
+CREATE INDEX IF NOT EXISTS idx_attempt_question_order_attempt ON attempt_question_order(attempt_id, position);
@@ -0,0 +1,6 @@
+-- ============================================================================
+-- QUIZ REVIEW SETTINGS
+-- ============================================================================
+-- Whether a completed attempt's review may show the correct answers.
+
+ALTER TABLE quizzes ADD COLUMN show_correct_answers BOOLEAN DEFAULT 1;
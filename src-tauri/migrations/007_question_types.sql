@@ -0,0 +1,44 @@
+-- ============================================================================
+-- QUESTION TYPES
+-- ============================================================================
+-- single_choice and true_false answers use selected_option_id,
+-- multiple_choice answers use selected_option_ids (a JSON array) and
+-- short_answer answers use text_answer, matched against accepted_answers
+-- (a JSON array of accepted variants).
+
+ALTER TABLE questions ADD COLUMN question_type TEXT NOT NULL DEFAULT 'single_choice'
+    CHECK(question_type IN ('single_choice', 'multiple_choice', 'true_false', 'short_answer'));
+
+ALTER TABLE questions ADD COLUMN accepted_answers TEXT;
+
+-- quiz_answers is rebuilt so selected_option_id can be NULL. The rebuilt
+-- table only references its attempt: questions and options are re-saved with
+-- INSERT OR REPLACE, which would otherwise cascade away saved answers.
+CREATE TABLE quiz_answers_new (
+                                id TEXT PRIMARY KEY,
+                                attempt_id TEXT NOT NULL,
+                                question_id TEXT NOT NULL,
+                                selected_option_id TEXT,
+                                selected_option_ids TEXT,
+                                text_answer TEXT,
+                                is_correct BOOLEAN DEFAULT 0,
+                                points_earned REAL DEFAULT 0,
+                                created_at TEXT NOT NULL,
+                                updated_at TEXT NOT NULL,
+                                answered_at TEXT,
+                                FOREIGN KEY (attempt_id) REFERENCES quiz_attempts(id) ON DELETE CASCADE,
+  UNIQUE(attempt_id, question_id)
+  );
+
+INSERT INTO quiz_answers_new
+    (id, attempt_id, question_id, selected_option_id, is_correct, points_earned,
+     created_at, updated_at, answered_at)
+SELECT id, attempt_id, question_id, selected_option_id, is_correct, points_earned,
+       created_at, updated_at, answered_at
+FROM quiz_answers;
+
+DROP TABLE quiz_answers;
+
+ALTER TABLE quiz_answers_new RENAME TO quiz_answers;
+
+CREATE INDEX IF NOT EXISTS idx_quiz_answers_attempt_id ON quiz_answers(attempt_id);
@@ -0,0 +1,24 @@
+-- ============================================================================
+-- COURSE PREREQUISITES
+-- ============================================================================
+-- Rebuilt without foreign keys: courses are re-saved with INSERT OR REPLACE,
+-- which would cascade away their prerequisites, and a prerequisite course is
+-- often not downloaded at all. Its title is kept so it can still be named.
+
+CREATE TABLE course_prerequisites_new (
+                                        id TEXT PRIMARY KEY,
+                                        course_id TEXT NOT NULL,
+                                        prerequisite_course_id TEXT NOT NULL,
+                                        prerequisite_title TEXT,
+                                        created_at TEXT NOT NULL,
+                                        UNIQUE(course_id, prerequisite_course_id)
+  );
+
+INSERT OR IGNORE INTO course_prerequisites_new (id, course_id, prerequisite_course_id, created_at)
+SELECT id, course_id, prerequisite_course_id, created_at FROM course_prerequisites;
+
+DROP TABLE course_prerequisites;
+
+ALTER TABLE course_prerequisites_new RENAME TO course_prerequisites;
+
+CREATE INDEX IF NOT EXISTS idx_course_prerequisites_course ON course_prerequisites(course_id);
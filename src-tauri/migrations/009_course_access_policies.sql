@@ -0,0 +1,14 @@
+-- ============================================================================
+-- COURSE ACCESS POLICIES
+-- ============================================================================
+-- How modules unlock within a course:
+--   free        - any module can be opened
+--   sequential  - a module opens once every earlier module is completed
+--   quiz_gated  - a module opens once every earlier module's quiz is passed
+-- Courses without a row use free navigation.
+
+CREATE TABLE IF NOT EXISTS course_access_policies (
+                                                    course_id TEXT PRIMARY KEY,
+                                                    policy TEXT NOT NULL DEFAULT 'free' CHECK(policy IN ('free', 'sequential', 'quiz_gated')),
+  updated_at TEXT NOT NULL
+  );
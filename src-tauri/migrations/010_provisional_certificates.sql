@@ -0,0 +1,28 @@
+-- ============================================================================
+-- PROVISIONAL CERTIFICATES
+-- ============================================================================
+-- Issued locally when a course is completed offline. The verification code is
+-- sent with the next sync; once the server confirms it, the row records the
+-- official certificate and the local PDF is discarded.
+
+CREATE TABLE IF NOT EXISTS provisional_certificates (
+                                                      id TEXT PRIMARY KEY,
+                                                      enrollment_id TEXT NOT NULL UNIQUE,
+                                                      user_id TEXT NOT NULL,
+                                                      course_id TEXT NOT NULL,
+                                                      student_name TEXT NOT NULL,
+                                                      course_title TEXT NOT NULL,
+                                                      completed_at TEXT NOT NULL,
+                                                      verification_code TEXT NOT NULL UNIQUE,
+                                                      file_path TEXT,
+                                                      status TEXT NOT NULL DEFAULT 'provisional' CHECK(status IN ('provisional', 'replaced')),
+  official_certificate_id TEXT,
+  official_certificate_number TEXT,
+  official_verification_token TEXT,
+  replaced_at TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+  );
+
+CREATE INDEX IF NOT EXISTS idx_provisional_certificates_course
+    ON provisional_certificates(user_id, course_id);
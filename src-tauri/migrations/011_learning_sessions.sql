@@ -0,0 +1,25 @@
+-- ============================================================================
+-- LEARNING SESSIONS
+-- ============================================================================
+-- One row per stretch of time spent on a content block. active_seconds only
+-- grows between heartbeats that arrive within the idle threshold. batch_id
+-- records which offline progress batch reported the time to the server.
+
+CREATE TABLE IF NOT EXISTS learning_sessions (
+                                               id TEXT PRIMARY KEY,
+                                               enrollment_id TEXT NOT NULL,
+                                               course_id TEXT NOT NULL,
+                                               module_id TEXT NOT NULL,
+                                               content_id TEXT NOT NULL,
+                                               started_at TEXT NOT NULL,
+                                               last_heartbeat_at TEXT NOT NULL,
+                                               ended_at TEXT,
+                                               active_seconds INTEGER NOT NULL DEFAULT 0,
+                                               batch_id INTEGER,
+                                               created_at TEXT NOT NULL,
+                                               updated_at TEXT NOT NULL
+  );
+
+CREATE INDEX IF NOT EXISTS idx_learning_sessions_enrollment ON learning_sessions(enrollment_id);
+CREATE INDEX IF NOT EXISTS idx_learning_sessions_course ON learning_sessions(course_id, batch_id);
+CREATE INDEX IF NOT EXISTS idx_learning_sessions_started ON learning_sessions(started_at);
@@ -0,0 +1,19 @@
+-- ============================================================================
+-- RESUME POSITIONS
+-- ============================================================================
+-- Where a learner left off inside a content block. Which column is used
+-- depends on position_type: media players store a timestamp, text blocks a
+-- scroll offset (0-1 fraction of the page height) and documents a page number.
+
+CREATE TABLE IF NOT EXISTS resume_positions (
+                                              enrollment_id TEXT NOT NULL,
+                                              content_id TEXT NOT NULL,
+                                              position_type TEXT NOT NULL CHECK(position_type IN ('media', 'scroll', 'page')),
+  media_seconds REAL,
+  scroll_offset REAL,
+  page_number INTEGER,
+  updated_at TEXT NOT NULL,
+  PRIMARY KEY (enrollment_id, content_id)
+  );
+
+CREATE INDEX IF NOT EXISTS idx_resume_positions_updated ON resume_positions(enrollment_id, updated_at);
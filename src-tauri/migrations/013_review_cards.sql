@@ -0,0 +1,28 @@
+-- ============================================================================
+-- REVIEW CARDS
+-- ============================================================================
+-- Spaced-repetition cards for quiz questions a learner got wrong, scheduled
+-- with SM-2 (ease_factor, interval_days, repetitions). A card is due once
+-- due_at has passed.
+
+CREATE TABLE IF NOT EXISTS review_cards (
+                                          id TEXT PRIMARY KEY,
+                                          student_id TEXT NOT NULL,
+                                          question_id TEXT NOT NULL,
+                                          quiz_id TEXT NOT NULL,
+                                          course_id TEXT,
+                                          ease_factor REAL NOT NULL DEFAULT 2.5,
+                                          interval_days INTEGER NOT NULL DEFAULT 0,
+                                          repetitions INTEGER NOT NULL DEFAULT 0,
+                                          lapses INTEGER NOT NULL DEFAULT 0,
+                                          review_count INTEGER NOT NULL DEFAULT 0,
+                                          due_at TEXT NOT NULL,
+                                          last_reviewed_at TEXT,
+                                          last_quality INTEGER,
+                                          source_attempt_id TEXT,
+                                          created_at TEXT NOT NULL,
+                                          updated_at TEXT NOT NULL,
+                                          UNIQUE(student_id, question_id)
+  );
+
+CREATE INDEX IF NOT EXISTS idx_review_cards_due ON review_cards(student_id, due_at);
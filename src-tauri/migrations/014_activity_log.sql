@@ -0,0 +1,44 @@
+-- ============================================================================
+-- ACTIVITY LOG
+-- ============================================================================
+-- One row per learning event, used for the activity calendar and streaks.
+-- Existing progress is backfilled so streaks include history from before the
+-- log existed.
+
+CREATE TABLE IF NOT EXISTS activity_log (
+                                          id INTEGER PRIMARY KEY AUTOINCREMENT,
+                                          student_id TEXT NOT NULL,
+                                          activity_type TEXT NOT NULL CHECK(activity_type IN ('content_viewed', 'content_completed', 'quiz_submitted')),
+  course_id TEXT,
+  reference_id TEXT NOT NULL,
+  occurred_at TEXT NOT NULL,
+  UNIQUE(student_id, activity_type, reference_id, occurred_at)
+  );
+
+CREATE INDEX IF NOT EXISTS idx_activity_log_student ON activity_log(student_id, occurred_at);
+
+CREATE TABLE IF NOT EXISTS weekly_goals (
+                                          student_id TEXT PRIMARY KEY,
+                                          target_minutes INTEGER NOT NULL DEFAULT 0,
+                                          target_items INTEGER NOT NULL DEFAULT 0,
+                                          updated_at TEXT NOT NULL
+);
+
+INSERT OR IGNORE INTO activity_log (student_id, activity_type, course_id, reference_id, occurred_at)
+SELECT e.student_id, 'content_viewed', e.course_id, cp.content_id, cp.viewed_at
+FROM content_progress cp
+         JOIN enrollments e ON cp.enrollment_id = e.id
+WHERE cp.viewed_at IS NOT NULL;
+
+INSERT OR IGNORE INTO activity_log (student_id, activity_type, course_id, reference_id, occurred_at)
+SELECT e.student_id, 'content_completed', e.course_id, cp.content_id, cp.completed_at
+FROM content_progress cp
+         JOIN enrollments e ON cp.enrollment_id = e.id
+WHERE cp.is_completed = 1 AND cp.completed_at IS NOT NULL;
+
+INSERT OR IGNORE INTO activity_log (student_id, activity_type, course_id, reference_id, occurred_at)
+SELECT qa.student_id, 'quiz_submitted', COALESCE(q.course_id, m.course_id), qa.id, qa.completed_at
+FROM quiz_attempts qa
+         JOIN quizzes q ON qa.quiz_id = q.id
+         LEFT JOIN modules m ON q.module_id = m.id
+WHERE qa.status = 'completed' AND qa.completed_at IS NOT NULL;
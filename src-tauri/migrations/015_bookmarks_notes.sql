@@ -0,0 +1,36 @@
+-- ============================================================================
+-- BOOKMARKS AND NOTES
+-- ============================================================================
+-- Learner annotations on content blocks, optionally pinned to a point in the
+-- block's media. Local changes are queued in sync_queue; rows arriving from
+-- the server are merged by updated_at.
+
+CREATE TABLE IF NOT EXISTS bookmarks (
+                                       id TEXT PRIMARY KEY,
+                                       student_id TEXT NOT NULL,
+                                       course_id TEXT NOT NULL,
+                                       module_id TEXT NOT NULL,
+                                       content_id TEXT NOT NULL,
+                                       media_seconds REAL,
+                                       label TEXT,
+                                       created_at TEXT NOT NULL,
+                                       updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_bookmarks_course ON bookmarks(student_id, course_id);
+CREATE INDEX IF NOT EXISTS idx_bookmarks_content ON bookmarks(student_id, content_id);
+
+CREATE TABLE IF NOT EXISTS notes (
+                                   id TEXT PRIMARY KEY,
+                                   student_id TEXT NOT NULL,
+                                   course_id TEXT NOT NULL,
+                                   module_id TEXT NOT NULL,
+                                   content_id TEXT NOT NULL,
+                                   media_seconds REAL,
+                                   body TEXT NOT NULL,
+                                   created_at TEXT NOT NULL,
+                                   updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_notes_course ON notes(student_id, course_id);
+CREATE INDEX IF NOT EXISTS idx_notes_content ON notes(student_id, content_id);
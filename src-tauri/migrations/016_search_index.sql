@@ -0,0 +1,16 @@
+-- ============================================================================
+-- FULL-TEXT SEARCH
+-- ============================================================================
+-- One row per searchable record (course, module, content block, question).
+-- Rows are written by the save commands; text pulled out of content_data is
+-- extracted in Rust, so the index is (re)built from there rather than here.
+
+CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+    title,
+    body,
+    kind UNINDEXED,
+    ref_id UNINDEXED,
+    course_id UNINDEXED,
+    module_id UNINDEXED,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
@@ -0,0 +1,12 @@
+-- ============================================================================
+-- COURSE CATALOGUE INDEXES
+-- ============================================================================
+-- Support query_courses: filters on category and level, and keyset
+-- pagination for each sort order. The popularity index is on the same
+-- expression the query orders by so it can be used.
+
+CREATE INDEX IF NOT EXISTS idx_courses_category ON courses(category);
+CREATE INDEX IF NOT EXISTS idx_courses_level ON courses(level);
+CREATE INDEX IF NOT EXISTS idx_courses_title ON courses(title COLLATE NOCASE, id);
+CREATE INDEX IF NOT EXISTS idx_courses_newest ON courses(created_at, id);
+CREATE INDEX IF NOT EXISTS idx_courses_popular ON courses(COALESCE(enrollment_count, 0), id);
@@ -0,0 +1,10 @@
+-- ============================================================================
+-- ACTIVE USER
+-- ============================================================================
+-- The signed-in learner is now recorded explicitly under 'active_user_id'.
+-- Until now logout deleted every user, so an existing install has at most one
+-- and that user is the one signed in.
+
+INSERT OR IGNORE INTO app_metadata (key, value)
+SELECT 'active_user_id', id FROM users
+WHERE (SELECT COUNT(*) FROM users) = 1;
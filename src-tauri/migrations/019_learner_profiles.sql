@@ -0,0 +1,15 @@
+-- ============================================================================
+-- LEARNER PROFILES
+-- ============================================================================
+-- Several learners can keep a cached profile on one device. Tokens now belong
+-- to a user so each profile keeps its own login, and users record when they
+-- last signed in so the profile picker can list the most recent first.
+
+ALTER TABLE auth_tokens ADD COLUMN user_id TEXT;
+ALTER TABLE users ADD COLUMN last_signed_in_at TEXT;
+
+UPDATE auth_tokens
+SET user_id = (SELECT value FROM app_metadata WHERE key = 'active_user_id')
+WHERE user_id IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_auth_tokens_user ON auth_tokens(user_id);
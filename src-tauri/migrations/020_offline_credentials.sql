@@ -0,0 +1,9 @@
+-- ============================================================================
+-- OFFLINE CREDENTIALS
+-- ============================================================================
+-- Switching to a cached profile requires that learner's password. A salted
+-- PBKDF2 hash is kept from their last online sign-in; repeated failures
+-- discard it so the profile needs a fresh online sign-in.
+
+ALTER TABLE users ADD COLUMN offline_credential TEXT;
+ALTER TABLE users ADD COLUMN credential_failures INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,7 @@
+-- ============================================================================
+-- OFFLINE SESSION GRACE PERIOD
+-- ============================================================================
+-- Hours a session stays usable after expires_at, as issued by the server with
+-- the offline package. NULL falls back to the app default; the app caps it.
+
+ALTER TABLE offline_sessions ADD COLUMN grace_period_hours INTEGER;
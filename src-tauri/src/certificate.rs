@@ -0,0 +1,195 @@
+use std::path::{Path, PathBuf};
+
+// ============================================================================
+// CERTIFICATE STORE
+// ============================================================================
+
+// Provisional certificates live next to the database:
+// <app_data_dir>/certificates/<certificate_id>.pdf
+pub fn get_certificate_dir(db_path: &str) -> Result<PathBuf, String> {
+    let app_data_dir = Path::new(db_path)
+        .parent()
+        .ok_or_else(|| format!("Invalid database path: {}", db_path))?;
+
+    Ok(app_data_dir.join("certificates"))
+}
+
+// Unambiguous characters only (no 0/O or 1/I), read out as XXXXX-XXXXX
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+pub fn generate_verification_code() -> Result<String, String> {
+    let mut bytes = [0u8; 10];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| format!("Failed to generate verification code: {}", e))?;
+
+    let code: String = bytes
+        .iter()
+        .map(|b| CODE_ALPHABET[*b as usize % CODE_ALPHABET.len()] as char)
+        .collect();
+
+    Ok(format!("{}-{}", &code[..5], &code[5..]))
+}
+
+// ============================================================================
+// PDF RENDERING
+// ============================================================================
+// A single landscape A4 page drawn with the standard Helvetica fonts, so the
+// file needs no embedded font data.
+
+pub struct CertificateDetails<'a> {
+    pub student_name: &'a str,
+    pub course_title: &'a str,
+    pub completed_at: &'a str,
+    pub verification_code: &'a str,
+}
+
+const PAGE_WIDTH: f64 = 842.0;
+const PAGE_HEIGHT: f64 = 595.0;
+
+// "2024-05-01T10:00:00Z" → "May 1, 2024"
+fn format_completion_date(completed_at: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(completed_at)
+        .map(|dt| dt.format("%B %-d, %Y").to_string())
+        .unwrap_or_else(|_| completed_at.to_string())
+}
+
+// WinAnsi string literal; characters outside Latin-1 become '?'
+fn pdf_string(text: &str) -> String {
+    let mut out = String::from("(");
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            '\u{a0}'..='\u{ff}' => out.push_str(&format!("\\{:03o}", c as u32)),
+            _ => out.push('?'),
+        }
+    }
+    out.push(')');
+    out
+}
+
+// Rough Helvetica advance widths, good enough to centre a line
+fn text_width(text: &str, size: f64) -> f64 {
+    let em: f64 = text
+        .chars()
+        .map(|c| match c {
+            'i' | 'j' | 'l' | '.' | ',' | '\'' | ':' | ';' | '!' | '|' => 0.25,
+            ' ' | 'f' | 't' | 'r' | 'I' | '-' | '(' | ')' => 0.33,
+            'm' | 'w' | 'M' | 'W' => 0.85,
+            'A'..='Z' => 0.68,
+            _ => 0.55,
+        })
+        .sum();
+
+    em * size
+}
+
+// Long names and titles are shrunk to fit inside the border
+fn centered_line(out: &mut String, font: &str, size: f64, y: f64, text: &str) {
+    let max_width = PAGE_WIDTH - 120.0;
+    let size = match text_width(text, size) {
+        width if width > max_width => (size * max_width / width).floor(),
+        _ => size,
+    };
+    let x = ((PAGE_WIDTH - text_width(text, size)) / 2.0).max(60.0);
+    out.push_str(&format!(
+        "BT /{} {} Tf {:.2} {:.2} Td {} Tj ET\n",
+        font,
+        size,
+        x,
+        y,
+        pdf_string(text)
+    ));
+}
+
+fn page_content(details: &CertificateDetails) -> String {
+    let mut out = String::new();
+
+    // Double border
+    out.push_str("0.15 0.25 0.45 RG 3 w 30 30 782 535 re S\n");
+    out.push_str("1 w 40 40 762 515 re S\n");
+
+    out.push_str("0.15 0.25 0.45 rg\n");
+    centered_line(&mut out, "F2", 34.0, 460.0, "CERTIFICATE OF COMPLETION");
+
+    out.push_str("0.2 0.2 0.2 rg\n");
+    centered_line(&mut out, "F1", 16.0, 400.0, "This certifies that");
+    centered_line(&mut out, "F2", 30.0, 350.0, details.student_name);
+    centered_line(&mut out, "F1", 16.0, 305.0, "has successfully completed the course");
+    centered_line(&mut out, "F2", 22.0, 262.0, details.course_title);
+    centered_line(
+        &mut out,
+        "F1",
+        14.0,
+        215.0,
+        &format!("Completed on {}", format_completion_date(details.completed_at)),
+    );
+
+    out.push_str("0.6 0.1 0.1 rg\n");
+    centered_line(
+        &mut out,
+        "F2",
+        12.0,
+        120.0,
+        "PROVISIONAL - issued offline and pending verification",
+    );
+
+    out.push_str("0.2 0.2 0.2 rg\n");
+    centered_line(
+        &mut out,
+        "F1",
+        12.0,
+        98.0,
+        &format!("Verification code: {}", details.verification_code),
+    );
+
+    out
+}
+
+pub fn render_certificate_pdf(details: &CertificateDetails) -> Vec<u8> {
+    let content = page_content(details);
+
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 4 0 R /F2 5 0 R >> >> /Contents 6 0 R >>",
+            PAGE_WIDTH, PAGE_HEIGHT
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+        format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ),
+    ];
+
+    let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", index + 1, object).as_bytes());
+    }
+
+    let xref_offset = pdf.len();
+    let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        xref.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    xref.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    ));
+    pdf.extend_from_slice(xref.as_bytes());
+
+    pdf
+}
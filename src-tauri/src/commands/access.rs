@@ -0,0 +1,673 @@
+use crate::commands::get_connection;
+use rusqlite::{params, Connection};
+
+// ============================================================================
+// OFFLINE SESSION POLICY
+// ============================================================================
+// While the app is offline, course content is only served under a valid
+// offline session for that course. Errors start with a stable code so the
+// frontend can tell a lapsed licence apart from an ordinary failure.
+
+pub const SESSION_EXPIRED: &str = "SESSION_EXPIRED";
+pub const SESSION_NOT_FOUND: &str = "SESSION_NOT_FOUND";
+pub const PREREQUISITES_NOT_MET: &str = "PREREQUISITES_NOT_MET";
+pub const MODULE_LOCKED: &str = "MODULE_LOCKED";
+
+pub const MODULE_POLICIES: &[&str] = &["free", "sequential", "quiz_gated"];
+
+// The server sends the grace period with each offline package; it is capped
+// so a tampered package cannot stretch an expired licence indefinitely
+pub const DEFAULT_GRACE_PERIOD_HOURS: i64 = 24;
+pub const MAX_GRACE_PERIOD_HOURS: i64 = 72;
+
+pub fn clamp_grace_period_hours(hours: Option<i64>) -> i64 {
+    hours
+        .unwrap_or(DEFAULT_GRACE_PERIOD_HOURS)
+        .clamp(0, MAX_GRACE_PERIOD_HOURS)
+}
+
+// SQL expression for when a session row stops being usable
+pub fn session_usable_until(table: &str) -> String {
+    format!(
+        "datetime({t}.expires_at, '+' || MIN(MAX(COALESCE({t}.grace_period_hours, {default}), 0), {max}) || ' hours')",
+        t = table,
+        default = DEFAULT_GRACE_PERIOD_HOURS,
+        max = MAX_GRACE_PERIOD_HOURS
+    )
+}
+
+// Online, the server checks enrolment on every request; the learner's access
+// token is the proof of that, not a flag the frontend sets
+fn has_live_access_token(conn: &Connection) -> bool {
+    let student_id = match current_student_id(conn) {
+        Some(student_id) => student_id,
+        None => return false,
+    };
+
+    conn.query_row(
+        "SELECT EXISTS(
+            SELECT 1 FROM auth_tokens
+            WHERE user_id = ?1
+              AND is_refresh_token = 0
+              AND datetime(expires_at) > datetime('now')
+         )",
+        params![student_id],
+        |row| row.get(0),
+    )
+    .unwrap_or(false)
+}
+
+fn session_expired_error() -> String {
+    format!(
+        "{}: Your offline access to this course has expired. Reconnect to renew it.",
+        SESSION_EXPIRED
+    )
+}
+
+// A session is usable until expires_at plus its grace period
+pub fn ensure_session_valid(conn: &Connection, session_id: &str) -> Result<(), String> {
+    let is_valid: bool = conn
+        .query_row(
+            &format!(
+                "SELECT EXISTS(
+                    SELECT 1 FROM offline_sessions
+                    WHERE id = ?1
+                      AND is_deleted = 0
+                      AND {} >= datetime('now')
+                 )",
+                session_usable_until("offline_sessions")
+            ),
+            params![session_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to check offline session: {}", e))?;
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(session_expired_error())
+    }
+}
+
+// Media encrypted under one profile's session stays playable while another
+// profile holds a valid session with the same key. Returns the session to
+// read the key from.
+pub fn ensure_media_session_valid(conn: &Connection, session_id: &str) -> Result<String, String> {
+    if ensure_session_valid(conn, session_id).is_ok() {
+        return Ok(session_id.to_string());
+    }
+
+    conn.query_row(
+        &format!(
+            "SELECT other.session_id FROM offline_session_keys own
+             JOIN offline_session_keys other
+               ON other.media_key = own.media_key AND other.session_id != own.session_id
+             JOIN offline_sessions s ON s.id = other.session_id
+             WHERE own.session_id = ?1
+               AND s.is_deleted = 0
+               AND {} >= datetime('now')
+             LIMIT 1",
+            session_usable_until("s")
+        ),
+        params![session_id],
+        |row| row.get(0),
+    )
+    .map_err(|_| session_expired_error())
+}
+
+// Gate for every command that returns course content
+pub fn ensure_offline_access(conn: &Connection, course_id: &str) -> Result<(), String> {
+    let (total_sessions, valid_sessions): (i64, i64) = conn
+        .query_row(
+            &format!(
+                "SELECT
+                    COUNT(*),
+                    COUNT(CASE
+                        WHEN is_deleted = 0 AND {} >= datetime('now') THEN 1
+                    END)
+                 FROM offline_sessions
+                 WHERE course_id = ?1",
+                session_usable_until("offline_sessions")
+            ),
+            params![course_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Failed to check offline session: {}", e))?;
+
+    if valid_sessions > 0 || has_live_access_token(conn) {
+        return Ok(());
+    }
+
+    if total_sessions == 0 {
+        return Err(format!(
+            "{}: This course has not been downloaded for offline use. Reconnect to access it.",
+            SESSION_NOT_FOUND
+        ));
+    }
+
+    Err(session_expired_error())
+}
+
+// ============================================================================
+// ACTIVE USER
+// ============================================================================
+// Several learners can have data on a shared device. The one signed in is
+// recorded in app_metadata, and all progress is read and written for that
+// learner only.
+
+pub const NOT_SIGNED_IN: &str = "NOT_SIGNED_IN";
+pub const WRONG_LEARNER: &str = "WRONG_LEARNER";
+
+const ACTIVE_USER_KEY: &str = "active_user_id";
+
+pub fn set_active_user(conn: &Connection, user_id: Option<&str>) -> Result<(), String> {
+    match user_id {
+        Some(user_id) => conn.execute(
+            "INSERT INTO app_metadata (key, value, updated_at)
+             VALUES (?1, ?2, datetime('now'))
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            params![ACTIVE_USER_KEY, user_id],
+        ),
+        None => conn.execute("DELETE FROM app_metadata WHERE key = ?1", params![ACTIVE_USER_KEY]),
+    }
+    .map_err(|e| format!("Failed to set active user: {}", e))?;
+
+    Ok(())
+}
+
+// The learner signed in on this device (see get_current_user)
+pub fn current_student_id(conn: &Connection) -> Option<String> {
+    conn.query_row(
+        "SELECT u.id FROM app_metadata m
+         JOIN users u ON u.id = m.value
+         WHERE m.key = ?1",
+        params![ACTIVE_USER_KEY],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+pub fn require_student_id(conn: &Connection) -> Result<String, String> {
+    current_student_id(conn).ok_or_else(|| format!("{}: No user is signed in", NOT_SIGNED_IN))
+}
+
+// Gate for commands that take a student id from the caller
+pub fn ensure_current_student(conn: &Connection, student_id: &str) -> Result<(), String> {
+    if require_student_id(conn)? != student_id {
+        return Err(format!(
+            "{}: Progress of another learner cannot be accessed",
+            WRONG_LEARNER
+        ));
+    }
+
+    Ok(())
+}
+
+// Gate for commands that take an enrollment id from the caller
+pub fn ensure_own_enrollment(conn: &Connection, enrollment_id: &str) -> Result<(), String> {
+    let student_id: String = conn
+        .query_row(
+            "SELECT student_id FROM enrollments WHERE id = ?1",
+            params![enrollment_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Enrollment not found: {}", e))?;
+
+    ensure_current_student(conn, &student_id)
+}
+
+// Gate for commands that take a quiz attempt id from the caller
+pub fn ensure_own_attempt(conn: &Connection, attempt_id: &str) -> Result<(), String> {
+    let student_id: String = conn
+        .query_row(
+            "SELECT student_id FROM quiz_attempts WHERE id = ?1",
+            params![attempt_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Attempt not found: {}", e))?;
+
+    ensure_current_student(conn, &student_id)
+}
+
+// ============================================================================
+// PREREQUISITES
+// ============================================================================
+
+pub struct Prerequisite {
+    pub course_id: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub is_completed: bool,
+}
+
+// A prerequisite is met by a local enrollment in it with status 'completed'
+pub fn get_prerequisite_status(
+    conn: &Connection,
+    course_id: &str,
+    student_id: &str,
+) -> Result<Vec<Prerequisite>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT cp.prerequisite_course_id,
+                    COALESCE(c.title, cp.prerequisite_title),
+                    c.description,
+                    m.public_url,
+                    EXISTS(
+                        SELECT 1 FROM enrollments e
+                        WHERE e.course_id = cp.prerequisite_course_id
+                          AND e.student_id = ?2
+                          AND e.status = 'completed'
+                    )
+             FROM course_prerequisites cp
+             LEFT JOIN courses c ON cp.prerequisite_course_id = c.id
+             LEFT JOIN course_media m ON c.image_id = m.id
+             WHERE cp.course_id = ?1
+             ORDER BY cp.created_at ASC",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let prerequisites: Vec<Prerequisite> = stmt
+        .query_map(params![course_id, student_id], |row| {
+            Ok(Prerequisite {
+                course_id: row.get(0)?,
+                title: row.get(1)?,
+                description: row.get(2)?,
+                image_url: row.get(3)?,
+                is_completed: row.get(4)?,
+            })
+        })
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(prerequisites)
+}
+
+pub fn ensure_prerequisites_met(conn: &Connection, course_id: &str) -> Result<(), String> {
+    // Nobody signed in means nobody has met them
+    let student_id = require_student_id(conn)?;
+
+    let missing: Vec<String> = get_prerequisite_status(conn, course_id, &student_id)?
+        .into_iter()
+        .filter(|p| !p.is_completed)
+        .map(|p| p.title.unwrap_or(p.course_id))
+        .collect();
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    Err(format!(
+        "{}: Complete {} before starting this course.",
+        PREREQUISITES_NOT_MET,
+        missing.join(", ")
+    ))
+}
+
+// Gate for commands that return lesson content: the offline session rules
+// plus the course's prerequisites
+pub fn ensure_content_access(conn: &Connection, course_id: &str) -> Result<(), String> {
+    ensure_offline_access(conn, course_id)?;
+    ensure_prerequisites_met(conn, course_id)
+}
+
+// ============================================================================
+// MODULE LOCKING
+// ============================================================================
+
+pub struct ModuleAccess {
+    pub module_id: String,
+    pub title: String,
+    pub order_index: i64,
+    // "locked", "unlocked" or "completed"
+    pub status: &'static str,
+    pub reason: &'static str,
+    pub message: String,
+}
+
+pub fn get_course_policy(conn: &Connection, course_id: &str) -> String {
+    conn.query_row(
+        "SELECT policy FROM course_access_policies WHERE course_id = ?1",
+        params![course_id],
+        |row| row.get(0),
+    )
+    .unwrap_or_else(|_| "free".to_string())
+}
+
+// The policy only ever arrives with the course's own sync data
+pub fn save_course_policy(conn: &Connection, course_id: &str, policy: &str) -> Result<(), String> {
+    if !MODULE_POLICIES.contains(&policy) {
+        return Err(format!(
+            "Unknown access policy '{}'. Expected one of: {}",
+            policy,
+            MODULE_POLICIES.join(", ")
+        ));
+    }
+
+    conn.execute(
+        "INSERT OR REPLACE INTO course_access_policies (course_id, policy, updated_at)
+         VALUES (?1, ?2, ?3)",
+        params![course_id, policy, chrono::Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| format!("Failed to set access policy: {}", e))?;
+
+    Ok(())
+}
+
+// The current learner's enrollment in a course, if any
+pub fn current_enrollment_id(conn: &Connection, course_id: &str) -> Option<String> {
+    let student_id = current_student_id(conn)?;
+
+    conn.query_row(
+        "SELECT id FROM enrollments
+         WHERE course_id = ?1 AND student_id = ?2
+         ORDER BY created_at DESC
+         LIMIT 1",
+        params![course_id, student_id],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+pub fn require_enrollment_id(conn: &Connection, course_id: &str) -> Result<String, String> {
+    require_student_id(conn)?;
+
+    current_enrollment_id(conn, course_id)
+        .ok_or_else(|| {
+            format!(
+                "Enrollment not found: the signed-in learner is not enrolled in course {}",
+                course_id
+            )
+        })
+}
+
+// The signed-in learner's enrollment in the course a quiz belongs to
+pub fn require_quiz_enrollment_id(conn: &Connection, quiz_id: &str) -> Result<String, String> {
+    let course_id: String = conn
+        .query_row(
+            "SELECT COALESCE(q.course_id, m.course_id) FROM quizzes q
+             LEFT JOIN modules m ON q.module_id = m.id
+             WHERE q.id = ?1",
+            params![quiz_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Quiz not found: {}", e))?;
+
+    require_enrollment_id(conn, &course_id)
+}
+
+// Walks the course's modules in order; the first module that fails the
+// policy locks every incomplete module after it
+pub fn get_module_access(
+    conn: &Connection,
+    enrollment_id: &str,
+) -> Result<(String, Vec<ModuleAccess>), String> {
+    let (course_id, student_id): (String, String) = conn
+        .query_row(
+            "SELECT course_id, student_id FROM enrollments WHERE id = ?1",
+            params![enrollment_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Enrollment not found: {}", e))?;
+
+    let policy = get_course_policy(conn, &course_id);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT m.id, m.title, m.order_index, COALESCE(m.has_quiz, 0),
+                    EXISTS(
+                        SELECT 1 FROM module_progress mp
+                        WHERE mp.module_id = m.id AND mp.enrollment_id = ?2
+                          AND mp.status = 'completed'
+                    ),
+                    EXISTS(
+                        SELECT 1 FROM quiz_attempts qa
+                        JOIN quizzes q ON qa.quiz_id = q.id
+                        WHERE q.module_id = m.id AND qa.student_id = ?3
+                          AND qa.status = 'completed' AND qa.passed = 1
+                    )
+             FROM modules m
+             WHERE m.course_id = ?1
+             ORDER BY m.order_index ASC, m.id ASC",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let rows: Vec<(String, String, i64, bool, bool, bool)> = stmt
+        .query_map(params![course_id, enrollment_id, student_id], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get::<_, Option<i64>>(2)?.unwrap_or(0),
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+            ))
+        })
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut modules = Vec::new();
+    // Set by the first module that blocks the ones after it
+    let mut blocker: Option<(&'static str, String)> = None;
+
+    for (index, (module_id, title, order_index, has_quiz, completed, quiz_passed)) in
+        rows.into_iter().enumerate()
+    {
+        let (status, reason, message) = if completed {
+            ("completed", "completed", "Module completed".to_string())
+        } else if let Some((reason, message)) = &blocker {
+            ("locked", *reason, message.clone())
+        } else if policy == "free" {
+            ("unlocked", "free_navigation", "All modules are open".to_string())
+        } else if index == 0 {
+            ("unlocked", "first_module", "First module of the course".to_string())
+        } else {
+            (
+                "unlocked",
+                "previous_requirements_met",
+                "Earlier modules are finished".to_string(),
+            )
+        };
+
+        if blocker.is_none() && policy != "free" {
+            if policy == "quiz_gated" && has_quiz && !quiz_passed {
+                blocker = Some(("quiz_not_passed", format!("Pass the quiz in \"{}\" first", title)));
+            } else if (policy == "sequential" || !has_quiz) && !completed {
+                blocker = Some((
+                    "previous_module_incomplete",
+                    format!("Complete \"{}\" first", title),
+                ));
+            }
+        }
+
+        modules.push(ModuleAccess {
+            module_id,
+            title,
+            order_index,
+            status,
+            reason,
+            message,
+        });
+    }
+
+    Ok((policy, modules))
+}
+
+fn ensure_module_unlocked(conn: &Connection, course_id: &str, module_id: &str) -> Result<(), String> {
+    if get_course_policy(conn, course_id) == "free" {
+        return Ok(());
+    }
+
+    // Without a local enrollment there is no progress to unlock anything with
+    let enrollment_id = current_enrollment_id(conn, course_id).ok_or_else(|| {
+        format!(
+            "{}: Enroll in this course to unlock its modules",
+            MODULE_LOCKED
+        )
+    })?;
+
+    let (_, modules) = get_module_access(conn, &enrollment_id)?;
+
+    match modules.iter().find(|m| m.module_id == module_id) {
+        Some(module) if module.status == "locked" => {
+            Err(format!("{}: {}", MODULE_LOCKED, module.message))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn ensure_module_access(conn: &Connection, module_id: &str) -> Result<(), String> {
+    let course_id: String = conn
+        .query_row(
+            "SELECT course_id FROM modules WHERE id = ?1",
+            params![module_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Module not found: {}", e))?;
+
+    ensure_content_access(conn, &course_id)?;
+    ensure_module_unlocked(conn, &course_id, module_id)
+}
+
+pub fn ensure_quiz_access(conn: &Connection, quiz_id: &str) -> Result<(), String> {
+    // Module quizzes only carry module_id, final exams only course_id
+    let (module_id, course_id): (Option<String>, String) = conn
+        .query_row(
+            "SELECT q.module_id, COALESCE(q.course_id, m.course_id) FROM quizzes q
+             LEFT JOIN modules m ON q.module_id = m.id
+             WHERE q.id = ?1",
+            params![quiz_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Quiz not found: {}", e))?;
+
+    // A module quiz is locked along with its module
+    match module_id {
+        Some(module_id) => {
+            ensure_content_access(conn, &course_id)?;
+            ensure_module_unlocked(conn, &course_id, &module_id)
+        }
+        None => ensure_content_access(conn, &course_id),
+    }
+}
+
+// ============================================================================
+// POLICY COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub fn check_course_offline_access(db_path: String, course_id: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let latest_session: Option<(String, String, String, i64)> = conn
+        .query_row(
+            &format!(
+                "SELECT id, expires_at, {}, grace_period_hours FROM offline_sessions
+                 WHERE course_id = ?1 AND is_deleted = 0
+                 ORDER BY datetime(expires_at) DESC
+                 LIMIT 1",
+                session_usable_until("offline_sessions")
+            ),
+            params![course_id],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    clamp_grace_period_hours(row.get(3)?),
+                ))
+            },
+        )
+        .ok();
+
+    let (allowed, error) = match ensure_offline_access(&conn, &course_id) {
+        Ok(()) => (true, None),
+        Err(e) => (false, Some(e)),
+    };
+
+    let status = serde_json::json!({
+        "course_id": course_id,
+        "allowed": allowed,
+        "error": error,
+        "has_online_access": has_live_access_token(&conn),
+        "grace_period_hours": latest_session.as_ref().map(|s| s.3),
+        "session_id": latest_session.as_ref().map(|s| s.0.clone()),
+        "expires_at": latest_session.as_ref().map(|s| s.1.clone()),
+        "grace_ends_at": latest_session.as_ref().map(|s| s.2.clone())
+    });
+
+    Ok(status.to_string())
+}
+
+#[tauri::command]
+pub fn get_course_access_policy(db_path: String, course_id: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    Ok(get_course_policy(&conn, &course_id))
+}
+
+#[tauri::command]
+pub fn get_module_access_map(db_path: String, enrollment_id: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    ensure_own_enrollment(&conn, &enrollment_id)?;
+
+    let (policy, modules) = get_module_access(&conn, &enrollment_id)?;
+
+    let modules: Vec<serde_json::Value> = modules
+        .into_iter()
+        .map(|m| {
+            serde_json::json!({
+                "module_id": m.module_id,
+                "title": m.title,
+                "order": m.order_index,
+                "status": m.status,
+                "reason": m.reason,
+                "message": m.message
+            })
+        })
+        .collect();
+
+    let access_map = serde_json::json!({
+        "enrollment_id": enrollment_id,
+        "policy": policy,
+        "modules": modules
+    });
+
+    Ok(access_map.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_course(policy: &str) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE app_metadata (key TEXT PRIMARY KEY, value TEXT, updated_at TEXT);
+             CREATE TABLE users (id TEXT PRIMARY KEY);
+             CREATE TABLE enrollments (id TEXT PRIMARY KEY, course_id TEXT, student_id TEXT, created_at TEXT);
+             CREATE TABLE course_access_policies (course_id TEXT PRIMARY KEY, policy TEXT, updated_at TEXT);
+             INSERT INTO users (id) VALUES ('u1');",
+        )
+        .unwrap();
+        set_active_user(&conn, Some("u1")).unwrap();
+        save_course_policy(&conn, "c1", policy).unwrap();
+        conn
+    }
+
+    #[test]
+    fn gated_modules_stay_locked_without_an_enrollment() {
+        let conn = open_course("sequential");
+        let err = ensure_module_unlocked(&conn, "c1", "mod1").unwrap_err();
+        assert!(err.starts_with(MODULE_LOCKED), "{}", err);
+
+        let conn = open_course("free");
+        assert!(ensure_module_unlocked(&conn, "c1", "mod1").is_ok());
+    }
+}
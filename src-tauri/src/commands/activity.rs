@@ -0,0 +1,873 @@
+use crate::commands::{access, get_connection};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use rusqlite::{params, Connection};
+use std::collections::BTreeMap;
+
+// ============================================================================
+// LEARNING SESSIONS
+// ============================================================================
+// The frontend starts a session when a content block is opened, sends a
+// heartbeat while the learner is active and stops it on leave. Gaps between
+// heartbeats longer than the idle threshold are not counted.
+
+pub const IDLE_THRESHOLD_SECONDS: i64 = 120;
+
+const SESSION_JSON: &str = "json_object(
+    'id', id,
+    'enrollment_id', enrollment_id,
+    'course_id', course_id,
+    'module_id', module_id,
+    'content_id', content_id,
+    'started_at', started_at,
+    'last_heartbeat_at', last_heartbeat_at,
+    'ended_at', ended_at,
+    'active_seconds', active_seconds,
+    'is_active', ended_at IS NULL
+)";
+
+fn session_json(conn: &Connection, session_id: &str) -> Result<String, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM learning_sessions WHERE id = ?1", SESSION_JSON),
+        params![session_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Learning session not found: {}", e))
+}
+
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+// Adds the time since the last heartbeat unless the learner went idle, and
+// optionally closes the session. Closed sessions are left untouched.
+fn record_activity(conn: &Connection, session_id: &str, close: bool) -> Result<(), String> {
+    let (last_heartbeat_at, ended_at): (String, Option<String>) = conn
+        .query_row(
+            "SELECT last_heartbeat_at, ended_at FROM learning_sessions WHERE id = ?1",
+            params![session_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Learning session not found: {}", e))?;
+
+    if ended_at.is_some() {
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let gap = parse_timestamp(&last_heartbeat_at)
+        .map(|last| (now - last).num_seconds().max(0))
+        .unwrap_or(0);
+    let credited = if gap <= IDLE_THRESHOLD_SECONDS { gap } else { 0 };
+    let now = now.to_rfc3339();
+
+    conn.execute(
+        "UPDATE learning_sessions
+         SET active_seconds = active_seconds + ?1,
+             last_heartbeat_at = ?2,
+             ended_at = CASE WHEN ?3 THEN ?2 END,
+             updated_at = ?2
+         WHERE id = ?4",
+        params![credited, now, close, session_id],
+    )
+    .map_err(|e| format!("Failed to update learning session: {}", e))?;
+
+    Ok(())
+}
+
+// Sessions abandoned without a stop (app closed, crash) end at their last
+// heartbeat
+fn close_stale_sessions(conn: &Connection) -> Result<usize, String> {
+    let cutoff = (Utc::now() - Duration::seconds(IDLE_THRESHOLD_SECONDS)).to_rfc3339();
+
+    conn.execute(
+        "UPDATE learning_sessions
+         SET ended_at = last_heartbeat_at, updated_at = ?1
+         WHERE ended_at IS NULL AND datetime(last_heartbeat_at) < datetime(?2)",
+        params![Utc::now().to_rfc3339(), cutoff],
+    )
+    .map_err(|e| format!("Failed to close stale sessions: {}", e))
+}
+
+// Ends a learner's open sessions when they sign out or another profile takes
+// over the device, so no time is credited to them afterwards
+pub fn close_learner_sessions(conn: &Connection, student_id: &str) -> Result<usize, String> {
+    let open_sessions: Vec<String> = conn
+        .prepare(
+            "SELECT ls.id FROM learning_sessions ls
+             JOIN enrollments e ON ls.enrollment_id = e.id
+             WHERE e.student_id = ?1 AND ls.ended_at IS NULL",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(params![student_id], |row| row.get(0))?
+                .collect()
+        })
+        .map_err(|e| format!("Failed to load open sessions: {}", e))?;
+
+    for session_id in &open_sessions {
+        record_activity(conn, session_id, true)?;
+    }
+
+    Ok(open_sessions.len())
+}
+
+#[tauri::command]
+pub fn start_learning_session(
+    db_path: String,
+    enrollment_id: String,
+    content_id: String,
+) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    access::ensure_own_enrollment(&conn, &enrollment_id)?;
+
+    let (module_id, course_id): (String, String) = conn
+        .query_row(
+            "SELECT cb.module_id, m.course_id FROM content_blocks cb
+             JOIN modules m ON cb.module_id = m.id
+             WHERE cb.id = ?1",
+            params![content_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Content block not found: {}", e))?;
+
+    let enrolled_course: String = conn
+        .query_row(
+            "SELECT course_id FROM enrollments WHERE id = ?1",
+            params![enrollment_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Enrollment not found: {}", e))?;
+
+    if enrolled_course != course_id {
+        return Err(format!(
+            "Content {} does not belong to the course of enrollment {}",
+            content_id, enrollment_id
+        ));
+    }
+
+    close_stale_sessions(&conn)?;
+
+    // Only one block is studied at a time; opening another one ends the last
+    let open_sessions: Vec<String> = conn
+        .prepare("SELECT id FROM learning_sessions WHERE enrollment_id = ?1 AND ended_at IS NULL")
+        .and_then(|mut stmt| {
+            stmt.query_map(params![enrollment_id], |row| row.get(0))?
+                .collect()
+        })
+        .map_err(|e| format!("Failed to load open sessions: {}", e))?;
+
+    for session_id in &open_sessions {
+        record_activity(&conn, session_id, true)?;
+    }
+
+    let now = Utc::now();
+    let session_id = format!("ls_{}_{}", content_id, now.timestamp_millis());
+    let now = now.to_rfc3339();
+
+    conn.execute(
+        "INSERT INTO learning_sessions
+         (id, enrollment_id, course_id, module_id, content_id, started_at, last_heartbeat_at,
+          active_seconds, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6, 0, ?6, ?6)",
+        params![session_id, enrollment_id, course_id, module_id, content_id, now],
+    )
+    .map_err(|e| format!("Failed to start learning session: {}", e))?;
+
+    session_json(&conn, &session_id)
+}
+
+// Sessions are only touched by the learner whose enrollment they belong to
+fn ensure_own_session(conn: &Connection, session_id: &str) -> Result<(), String> {
+    let enrollment_id: String = conn
+        .query_row(
+            "SELECT e.id FROM learning_sessions ls
+             JOIN enrollments e ON e.id = ls.enrollment_id
+             WHERE ls.id = ?1",
+            params![session_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Learning session not found: {}", e))?;
+
+    access::ensure_own_enrollment(conn, &enrollment_id)
+}
+
+#[tauri::command]
+pub fn heartbeat_learning_session(db_path: String, session_id: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    ensure_own_session(&conn, &session_id)?;
+    record_activity(&conn, &session_id, false)?;
+
+    session_json(&conn, &session_id)
+}
+
+#[tauri::command]
+pub fn stop_learning_session(db_path: String, session_id: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    ensure_own_session(&conn, &session_id)?;
+    record_activity(&conn, &session_id, true)?;
+
+    session_json(&conn, &session_id)
+}
+
+// ============================================================================
+// TIME SPENT
+// ============================================================================
+
+#[tauri::command]
+pub fn get_time_spent(db_path: String, enrollment_id: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    access::ensure_own_enrollment(&conn, &enrollment_id)?;
+
+    let course_id: String = conn
+        .query_row(
+            "SELECT course_id FROM enrollments WHERE id = ?1",
+            params![enrollment_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Enrollment not found: {}", e))?;
+
+    let total_seconds: i64 = conn
+        .query_row(
+            "SELECT COALESCE(SUM(active_seconds), 0) FROM learning_sessions WHERE enrollment_id = ?1",
+            params![enrollment_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to total time spent: {}", e))?;
+
+    let modules: Vec<String> = conn
+        .prepare(
+            "SELECT json_object(
+                'module_id', module_id,
+                'seconds', SUM(active_seconds),
+                'session_count', COUNT(*)
+             ) FROM learning_sessions
+             WHERE enrollment_id = ?1
+             GROUP BY module_id
+             ORDER BY module_id",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(params![enrollment_id], |row| row.get(0))?
+                .collect()
+        })
+        .map_err(|e| format!("Failed to total time per module: {}", e))?;
+
+    let contents: Vec<String> = conn
+        .prepare(
+            "SELECT json_object(
+                'content_id', content_id,
+                'module_id', module_id,
+                'seconds', SUM(active_seconds),
+                'session_count', COUNT(*),
+                'last_studied_at', MAX(last_heartbeat_at)
+             ) FROM learning_sessions
+             WHERE enrollment_id = ?1
+             GROUP BY content_id
+             ORDER BY content_id",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(params![enrollment_id], |row| row.get(0))?
+                .collect()
+        })
+        .map_err(|e| format!("Failed to total time per content: {}", e))?;
+
+    Ok(format!(
+        r#"{{"enrollment_id":{},"course_id":{},"total_seconds":{},"modules":[{}],"contents":[{}]}}"#,
+        serde_json::json!(enrollment_id),
+        serde_json::json!(course_id),
+        total_seconds,
+        modules.join(","),
+        contents.join(",")
+    ))
+}
+
+// ============================================================================
+// LOCAL DAYS
+// ============================================================================
+// Timestamps are stored in UTC; days, weeks and streaks follow the learner's
+// clock, given as an offset in minutes (e.g. 120 for UTC+2).
+
+// Real UTC offsets stay within 14 hours either way
+const MAX_UTC_OFFSET_MINUTES: i64 = 14 * 60;
+
+fn local_offset(utc_offset_minutes: Option<i64>) -> Result<Duration, String> {
+    match utc_offset_minutes.unwrap_or(0) {
+        minutes if (-MAX_UTC_OFFSET_MINUTES..=MAX_UTC_OFFSET_MINUTES).contains(&minutes) => {
+            Ok(Duration::minutes(minutes))
+        }
+        minutes => Err(format!("Invalid UTC offset: {} minutes", minutes)),
+    }
+}
+
+fn local_today(offset: Duration) -> NaiveDate {
+    (Utc::now() + offset).date_naive()
+}
+
+// Monday of the local week containing `day`
+fn week_start(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+// The UTC instant at which a local day begins
+fn local_midnight_utc(day: NaiveDate, offset: Duration) -> Result<DateTime<Utc>, String> {
+    day.and_hms_opt(0, 0, 0)
+        .map(|dt| dt.and_utc() - offset)
+        .ok_or_else(|| format!("Invalid date: {}", day))
+}
+
+// SQLite date() modifier that shifts a UTC timestamp onto the local day
+fn day_modifier(offset: Duration) -> String {
+    format!("{:+} minutes", offset.num_minutes())
+}
+
+fn parse_day(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|e| format!("Invalid date '{}': {}", value, e))
+}
+
+// Time the current learner studied since Monday, bucketed by local day
+#[tauri::command]
+pub fn get_weekly_study_time(
+    db_path: String,
+    utc_offset_minutes: Option<i64>,
+) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let student_id = access::require_student_id(&conn)?;
+    let offset = local_offset(utc_offset_minutes)?;
+
+    let week_start = week_start(local_today(offset));
+    let week_start_utc = local_midnight_utc(week_start, offset)?;
+
+    let modifier = day_modifier(offset);
+
+    let per_day: Vec<(String, i64)> = conn
+        .prepare(
+            "SELECT date(ls.started_at, ?3), SUM(ls.active_seconds)
+             FROM learning_sessions ls
+             JOIN enrollments e ON ls.enrollment_id = e.id
+             WHERE e.student_id = ?1 AND datetime(ls.started_at) >= datetime(?2)
+             GROUP BY 1",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(
+                params![student_id, week_start_utc.to_rfc3339(), modifier],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?
+            .collect()
+        })
+        .map_err(|e| format!("Failed to total weekly time: {}", e))?;
+
+    let days: Vec<serde_json::Value> = (0..7)
+        .map(|i| {
+            let date = (week_start + Duration::days(i)).format("%Y-%m-%d").to_string();
+            let seconds = per_day
+                .iter()
+                .find(|(day, _)| *day == date)
+                .map(|(_, s)| *s)
+                .unwrap_or(0);
+            serde_json::json!({ "date": date, "seconds": seconds })
+        })
+        .collect();
+
+    let weekly = serde_json::json!({
+        "week_start": week_start.format("%Y-%m-%d").to_string(),
+        "utc_offset_minutes": offset.num_minutes(),
+        "total_seconds": per_day.iter().map(|(_, s)| s).sum::<i64>(),
+        "days": days
+    });
+
+    Ok(weekly.to_string())
+}
+
+// Hands the closed, not yet reported sessions of a course to a progress batch
+// and returns the seconds they add up to
+pub fn assign_unreported_time(
+    conn: &Connection,
+    course_id: &str,
+    offline_session_id: &str,
+    batch_id: i64,
+) -> Result<i64, String> {
+    conn.execute(
+        "UPDATE learning_sessions
+         SET batch_id = ?3
+         WHERE course_id = ?1
+           AND batch_id IS NULL
+           AND ended_at IS NOT NULL
+           AND enrollment_id IN (
+               SELECT e.id FROM enrollments e
+               JOIN offline_sessions os ON os.student_id = e.student_id
+               WHERE os.id = ?2 AND e.course_id = ?1
+           )",
+        params![course_id, offline_session_id, batch_id],
+    )
+    .map_err(|e| format!("Failed to assign learning time: {}", e))?;
+
+    conn.query_row(
+        "SELECT COALESCE(SUM(active_seconds), 0) FROM learning_sessions WHERE batch_id = ?1",
+        params![batch_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Failed to total learning time: {}", e))
+}
+
+// ============================================================================
+// ACTIVITY LOG
+// ============================================================================
+
+pub fn log_activity(
+    conn: &Connection,
+    student_id: &str,
+    activity_type: &str,
+    course_id: Option<&str>,
+    reference_id: &str,
+    occurred_at: &str,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO activity_log
+         (student_id, activity_type, course_id, reference_id, occurred_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![student_id, activity_type, course_id, reference_id, occurred_at],
+    )
+    .map_err(|e| format!("Failed to log activity: {}", e))?;
+
+    Ok(())
+}
+
+pub fn log_enrollment_activity(
+    conn: &Connection,
+    enrollment_id: &str,
+    activity_type: &str,
+    reference_id: &str,
+    occurred_at: &str,
+) -> Result<(), String> {
+    let (student_id, course_id): (String, String) = conn
+        .query_row(
+            "SELECT student_id, course_id FROM enrollments WHERE id = ?1",
+            params![enrollment_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Enrollment not found: {}", e))?;
+
+    log_activity(conn, &student_id, activity_type, Some(&course_id), reference_id, occurred_at)
+}
+
+// Logged at the attempt's completion time, so grading it again adds nothing
+pub fn log_quiz_submission(conn: &Connection, attempt_id: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO activity_log
+         (student_id, activity_type, course_id, reference_id, occurred_at)
+         SELECT qa.student_id, 'quiz_submitted', COALESCE(q.course_id, m.course_id), qa.id, qa.completed_at
+         FROM quiz_attempts qa
+         JOIN quizzes q ON qa.quiz_id = q.id
+         LEFT JOIN modules m ON q.module_id = m.id
+         WHERE qa.id = ?1 AND qa.completed_at IS NOT NULL",
+        params![attempt_id],
+    )
+    .map_err(|e| format!("Failed to log activity: {}", e))?;
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct DayActivity {
+    seconds: i64,
+    content_viewed: i64,
+    content_completed: i64,
+    quiz_submitted: i64,
+}
+
+impl DayActivity {
+    fn items(&self) -> i64 {
+        self.content_viewed + self.content_completed + self.quiz_submitted
+    }
+}
+
+// Study time and logged events per local day in [from, to)
+fn daily_activity(
+    conn: &Connection,
+    student_id: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    offset: Duration,
+) -> Result<BTreeMap<String, DayActivity>, String> {
+    let modifier = day_modifier(offset);
+    let (from, to) = (from.to_rfc3339(), to.to_rfc3339());
+    let mut days: BTreeMap<String, DayActivity> = BTreeMap::new();
+
+    let seconds: Vec<(String, i64)> = conn
+        .prepare(
+            "SELECT date(ls.started_at, ?4), SUM(ls.active_seconds)
+             FROM learning_sessions ls
+             JOIN enrollments e ON ls.enrollment_id = e.id
+             WHERE e.student_id = ?1
+               AND datetime(ls.started_at) >= datetime(?2)
+               AND datetime(ls.started_at) < datetime(?3)
+             GROUP BY 1",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(params![student_id, from, to, modifier], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect()
+        })
+        .map_err(|e| format!("Failed to total study time: {}", e))?;
+
+    for (day, total) in seconds {
+        days.entry(day).or_default().seconds = total;
+    }
+
+    let events: Vec<(String, String, i64)> = conn
+        .prepare(
+            "SELECT date(occurred_at, ?4), activity_type, COUNT(*)
+             FROM activity_log
+             WHERE student_id = ?1
+               AND datetime(occurred_at) >= datetime(?2)
+               AND datetime(occurred_at) < datetime(?3)
+             GROUP BY 1, 2",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(params![student_id, from, to, modifier], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect()
+        })
+        .map_err(|e| format!("Failed to count activity: {}", e))?;
+
+    for (day, activity_type, count) in events {
+        let entry = days.entry(day).or_default();
+        match activity_type.as_str() {
+            "content_viewed" => entry.content_viewed = count,
+            "content_completed" => entry.content_completed = count,
+            _ => entry.quiz_submitted += count,
+        }
+    }
+
+    Ok(days)
+}
+
+// One entry per local day between from_date and to_date (inclusive, YYYY-MM-DD)
+#[tauri::command]
+pub fn get_activity_calendar(
+    db_path: String,
+    student_id: String,
+    from_date: String,
+    to_date: String,
+    utc_offset_minutes: Option<i64>,
+) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    access::ensure_current_student(&conn, &student_id)?;
+
+    let (from, to) = (parse_day(&from_date)?, parse_day(&to_date)?);
+    if to < from {
+        return Err("to_date must not be before from_date".to_string());
+    }
+    if (to - from).num_days() > 366 {
+        return Err("Calendar range is limited to one year".to_string());
+    }
+
+    let offset = local_offset(utc_offset_minutes)?;
+    let activity = daily_activity(
+        &conn,
+        &student_id,
+        local_midnight_utc(from, offset)?,
+        local_midnight_utc(to + Duration::days(1), offset)?,
+        offset,
+    )?;
+
+    let empty = DayActivity::default();
+    let days: Vec<serde_json::Value> = from
+        .iter_days()
+        .take_while(|day| *day <= to)
+        .map(|day| {
+            let date = day.format("%Y-%m-%d").to_string();
+            let entry = activity.get(&date).unwrap_or(&empty);
+            serde_json::json!({
+                "date": date,
+                "minutes": entry.seconds / 60,
+                "items": entry.items(),
+                "content_viewed": entry.content_viewed,
+                "content_completed": entry.content_completed,
+                "quiz_submitted": entry.quiz_submitted,
+                "is_active": entry.seconds > 0 || entry.items() > 0
+            })
+        })
+        .collect();
+
+    Ok(serde_json::Value::Array(days).to_string())
+}
+
+// ============================================================================
+// STREAKS
+// ============================================================================
+
+// A streak is a run of consecutive local days with any logged activity or
+// study time. Today still counts as part of the current streak until it ends.
+#[tauri::command]
+pub fn get_learning_streak(
+    db_path: String,
+    student_id: String,
+    utc_offset_minutes: Option<i64>,
+) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    access::ensure_current_student(&conn, &student_id)?;
+
+    let offset = local_offset(utc_offset_minutes)?;
+
+    let active_days: Vec<NaiveDate> = conn
+        .prepare(
+            "SELECT day FROM (
+                SELECT date(occurred_at, ?2) AS day FROM activity_log WHERE student_id = ?1
+                UNION
+                SELECT date(ls.started_at, ?2) FROM learning_sessions ls
+                JOIN enrollments e ON ls.enrollment_id = e.id
+                WHERE e.student_id = ?1 AND ls.active_seconds > 0
+             )
+             WHERE day IS NOT NULL
+             ORDER BY day ASC",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(params![student_id, day_modifier(offset)], |row| {
+                row.get::<_, String>(0)
+            })?
+            .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| format!("Failed to load active days: {}", e))?
+        .iter()
+        .filter_map(|day| parse_day(day).ok())
+        .collect();
+
+    let mut longest_streak = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+
+    for day in &active_days {
+        run = match previous {
+            Some(prev) if *day - prev == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest_streak = longest_streak.max(run);
+        previous = Some(*day);
+    }
+
+    let today = local_today(offset);
+    let last_active = active_days.last().copied();
+
+    // The run ending on the last active day is current only if that was today
+    // or yesterday
+    let current_streak = match last_active {
+        Some(day) if today - day <= Duration::days(1) => run,
+        _ => 0,
+    };
+
+    let streak = serde_json::json!({
+        "current_streak": current_streak,
+        "longest_streak": longest_streak,
+        "active_today": last_active == Some(today),
+        "last_active_date": last_active.map(|d| d.format("%Y-%m-%d").to_string()),
+        "total_active_days": active_days.len()
+    });
+
+    Ok(streak.to_string())
+}
+
+// ============================================================================
+// WEEKLY GOALS
+// ============================================================================
+
+#[tauri::command]
+pub fn set_weekly_goal(
+    db_path: String,
+    student_id: String,
+    target_minutes: i64,
+    target_items: Option<i64>,
+) -> Result<String, String> {
+    if target_minutes < 0 || target_items.unwrap_or(0) < 0 {
+        return Err("Weekly goal targets cannot be negative".to_string());
+    }
+
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    access::ensure_current_student(&conn, &student_id)?;
+
+    conn.execute(
+        "INSERT INTO weekly_goals (student_id, target_minutes, target_items, updated_at)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(student_id) DO UPDATE SET
+            target_minutes = excluded.target_minutes,
+            target_items = excluded.target_items,
+            updated_at = excluded.updated_at",
+        params![
+            student_id,
+            target_minutes,
+            target_items.unwrap_or(0),
+            Utc::now().to_rfc3339()
+        ],
+    )
+    .map_err(|e| format!("Failed to save weekly goal: {}", e))?;
+
+    Ok("Weekly goal saved successfully".to_string())
+}
+
+#[tauri::command]
+pub fn get_weekly_goal_progress(
+    db_path: String,
+    student_id: String,
+    utc_offset_minutes: Option<i64>,
+) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    access::ensure_current_student(&conn, &student_id)?;
+
+    let goal: Option<(i64, i64)> = conn
+        .query_row(
+            "SELECT target_minutes, target_items FROM weekly_goals WHERE student_id = ?1",
+            params![student_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok();
+    let (target_minutes, target_items) = goal.unwrap_or((0, 0));
+
+    let offset = local_offset(utc_offset_minutes)?;
+    let week_start = week_start(local_today(offset));
+    let activity = daily_activity(
+        &conn,
+        &student_id,
+        local_midnight_utc(week_start, offset)?,
+        local_midnight_utc(week_start + Duration::days(7), offset)?,
+        offset,
+    )?;
+
+    let minutes: i64 = activity.values().map(|d| d.seconds).sum::<i64>() / 60;
+    let items: i64 = activity.values().map(|d| d.items()).sum();
+
+    let percentage = |done: i64, target: i64| -> f64 {
+        if target > 0 {
+            ((done as f64 / target as f64) * 100.0).min(100.0).round()
+        } else {
+            0.0
+        }
+    };
+
+    // A target of 0 means that part of the goal is not used
+    let achieved = goal.is_some()
+        && (target_minutes > 0 || target_items > 0)
+        && minutes >= target_minutes
+        && items >= target_items;
+
+    let progress = serde_json::json!({
+        "goal_set": goal.is_some(),
+        "week_start": week_start.format("%Y-%m-%d").to_string(),
+        "target_minutes": target_minutes,
+        "target_items": target_items,
+        "minutes": minutes,
+        "items": items,
+        "minutes_percentage": percentage(minutes, target_minutes),
+        "items_percentage": percentage(items, target_items),
+        "achieved": achieved,
+        "active_days": activity.values().filter(|d| d.seconds > 0 || d.items() > 0).count()
+    });
+
+    Ok(progress.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(value: &str) -> NaiveDate {
+        parse_day(value).unwrap()
+    }
+
+    // What SQLite files a UTC timestamp under for the given offset
+    fn local_date(timestamp: &str, offset: Duration) -> String {
+        Connection::open_in_memory()
+            .unwrap()
+            .query_row("SELECT date(?1, ?2)", params![timestamp, day_modifier(offset)], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn offsets_are_bounded() {
+        assert_eq!(local_offset(None), Ok(Duration::zero()));
+        assert_eq!(local_offset(Some(-720)), Ok(Duration::hours(-12)));
+        assert_eq!(local_offset(Some(840)), Ok(Duration::hours(14)));
+        assert!(local_offset(Some(841)).is_err());
+        assert!(local_offset(Some(i64::MIN)).is_err());
+        assert!(local_offset(Some(i64::MAX)).is_err());
+    }
+
+    #[test]
+    fn weeks_start_on_monday() {
+        assert_eq!(week_start(day("2024-01-01")), day("2024-01-01"));
+        assert_eq!(week_start(day("2024-01-07")), day("2024-01-01"));
+        assert_eq!(week_start(day("2024-01-08")), day("2024-01-08"));
+        assert_eq!(week_start(day("2024-03-01")), day("2024-02-26"));
+    }
+
+    #[test]
+    fn local_midnight_moves_against_the_offset() {
+        let midnight = |offset| local_midnight_utc(day("2024-03-10"), offset).unwrap().to_rfc3339();
+        assert_eq!(midnight(Duration::zero()), "2024-03-10T00:00:00+00:00");
+        assert_eq!(midnight(Duration::hours(2)), "2024-03-09T22:00:00+00:00");
+        assert_eq!(midnight(Duration::minutes(-330)), "2024-03-10T05:30:00+00:00");
+    }
+
+    #[test]
+    fn day_modifier_is_signed_minutes() {
+        assert_eq!(day_modifier(Duration::zero()), "+0 minutes");
+        assert_eq!(day_modifier(Duration::hours(2)), "+120 minutes");
+        assert_eq!(day_modifier(Duration::minutes(-570)), "-570 minutes");
+    }
+
+    #[test]
+    fn timestamps_land_on_the_local_day() {
+        let late = "2024-03-10T23:30:00+00:00";
+        assert_eq!(local_date(late, Duration::zero()), "2024-03-10");
+        assert_eq!(local_date(late, Duration::hours(1)), "2024-03-11");
+        assert_eq!(local_date(late, Duration::hours(-12)), "2024-03-10");
+
+        let early = "2024-03-11T00:15:00+00:00";
+        assert_eq!(local_date(early, Duration::minutes(-30)), "2024-03-10");
+        assert_eq!(local_date(early, Duration::hours(14)), "2024-03-11");
+    }
+
+    #[test]
+    fn the_local_day_boundary_matches_sqlite() {
+        // A timestamp at local midnight belongs to that day, one second
+        // earlier to the day before
+        for minutes in [-720, -330, 0, 60, 345, 840] {
+            let offset = Duration::minutes(minutes);
+            let midnight = local_midnight_utc(day("2024-01-01"), offset).unwrap();
+            assert_eq!(local_date(&midnight.to_rfc3339(), offset), "2024-01-01");
+            let before = (midnight - Duration::seconds(1)).to_rfc3339();
+            assert_eq!(local_date(&before, offset), "2023-12-31");
+        }
+    }
+
+    #[test]
+    fn today_follows_the_offset() {
+        let utc_today = Utc::now().date_naive();
+        let ahead = local_today(Duration::hours(14));
+        let behind = local_today(Duration::hours(-12));
+        assert!(ahead >= utc_today && ahead - utc_today <= Duration::days(1));
+        assert!(behind <= utc_today && utc_today - behind <= Duration::days(1));
+    }
+}
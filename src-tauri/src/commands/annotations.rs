@@ -0,0 +1,497 @@
+use crate::commands::{access, get_connection, sync};
+use rusqlite::{params, Connection};
+use serde_json::Value as JsonValue;
+
+// ============================================================================
+// BOOKMARKS AND NOTES
+// ============================================================================
+// Both are annotations on a content block and share the same storage layout;
+// they differ only in their text column (an optional label for bookmarks, a
+// required body for notes). Every local change is queued for sync.
+
+struct AnnotationKind {
+    table: &'static str,
+    id_prefix: &'static str,
+    text_column: &'static str,
+    text_required: bool,
+}
+
+const BOOKMARK: AnnotationKind = AnnotationKind {
+    table: "bookmarks",
+    id_prefix: "bm",
+    text_column: "label",
+    text_required: false,
+};
+
+const NOTE: AnnotationKind = AnnotationKind {
+    table: "notes",
+    id_prefix: "note",
+    text_column: "body",
+    text_required: true,
+};
+
+// Orders annotations the way the course is laid out
+const COURSE_ORDER: &str =
+    "m.order_index ASC, cb.order_index ASC, a.media_seconds IS NULL, a.media_seconds ASC, a.created_at ASC";
+
+fn annotation_json_sql(kind: &AnnotationKind) -> String {
+    format!(
+        "SELECT json_object(
+            'id', a.id,
+            'student_id', a.student_id,
+            'course_id', a.course_id,
+            'module_id', a.module_id,
+            'module_title', m.title,
+            'content_id', a.content_id,
+            'content_title', cb.title,
+            'media_seconds', a.media_seconds,
+            '{col}', a.{col},
+            'created_at', a.created_at,
+            'updated_at', a.updated_at
+         ) FROM {table} a
+         LEFT JOIN content_blocks cb ON a.content_id = cb.id
+         LEFT JOIN modules m ON a.module_id = m.id",
+        col = kind.text_column,
+        table = kind.table
+    )
+}
+
+fn load_annotation(conn: &Connection, kind: &AnnotationKind, id: &str) -> Result<String, String> {
+    conn.query_row(
+        &format!("{} WHERE a.id = ?1", annotation_json_sql(kind)),
+        params![id],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("{} entry {} not found: {}", kind.table, id, e))
+}
+
+fn list_annotations(
+    conn: &Connection,
+    kind: &AnnotationKind,
+    filter: &str,
+    student_id: &str,
+    value: &str,
+) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE a.student_id = ?1 AND a.{} = ?2 ORDER BY {}",
+            annotation_json_sql(kind),
+            filter,
+            COURSE_ORDER
+        ))
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![student_id, value], |row| row.get(0))
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(rows)
+}
+
+fn read_text(kind: &AnnotationKind, data: &JsonValue) -> Result<Option<String>, String> {
+    let text = data[kind.text_column]
+        .as_str()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+
+    if kind.text_required && text.is_none() {
+        return Err(format!("Missing {}", kind.text_column));
+    }
+
+    Ok(text)
+}
+
+fn read_media_seconds(data: &JsonValue) -> Result<Option<f64>, String> {
+    match data["media_seconds"].as_f64() {
+        Some(seconds) if seconds < 0.0 => Err("media_seconds cannot be negative".to_string()),
+        other => Ok(other),
+    }
+}
+
+fn queue_annotation(
+    conn: &Connection,
+    kind: &AnnotationKind,
+    operation: &str,
+    id: &str,
+    data: &str,
+) -> Result<(), String> {
+    sync::queue_change(conn, operation, kind.table, id, data)
+}
+
+fn create_annotation(conn: &Connection, kind: &AnnotationKind, data: &str) -> Result<String, String> {
+    let data: JsonValue = serde_json::from_str(data).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let student_id = access::require_student_id(conn)?;
+    let content_id = data["content_id"].as_str().ok_or("Missing content_id")?;
+    let text = read_text(kind, &data)?;
+    let media_seconds = read_media_seconds(&data)?;
+
+    let (module_id, course_id): (String, String) = conn
+        .query_row(
+            "SELECT cb.module_id, m.course_id FROM content_blocks cb
+             JOIN modules m ON cb.module_id = m.id
+             WHERE cb.id = ?1",
+            params![content_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Content block not found: {}", e))?;
+
+    let now = chrono::Utc::now();
+    let id = data["id"]
+        .as_str()
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| format!("{}_{}_{}", kind.id_prefix, content_id, now.timestamp_millis()));
+    let now = now.to_rfc3339();
+
+    conn.execute(
+        &format!(
+            "INSERT INTO {} (id, student_id, course_id, module_id, content_id, media_seconds, {}, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)",
+            kind.table, kind.text_column
+        ),
+        params![id, student_id, course_id, module_id, content_id, media_seconds, text, now],
+    )
+    .map_err(|e| format!("Failed to save {}: {}", kind.table, e))?;
+
+    let saved = load_annotation(conn, kind, &id)?;
+    queue_annotation(conn, kind, "create", &id, &saved)?;
+
+    Ok(saved)
+}
+
+fn update_annotation(
+    conn: &Connection,
+    kind: &AnnotationKind,
+    id: &str,
+    data: &str,
+) -> Result<String, String> {
+    let data: JsonValue = serde_json::from_str(data).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let student_id = access::require_student_id(conn)?;
+    let text = read_text(kind, &data)?;
+    let media_seconds = read_media_seconds(&data)?;
+
+    // Fields left out of the update keep their value
+    let updated = conn
+        .execute(
+            &format!(
+                "UPDATE {table}
+                 SET {col} = CASE WHEN ?1 THEN ?2 ELSE {col} END,
+                     media_seconds = CASE WHEN ?3 THEN ?4 ELSE media_seconds END,
+                     updated_at = ?5
+                 WHERE id = ?6 AND student_id = ?7",
+                table = kind.table,
+                col = kind.text_column
+            ),
+            params![
+                !data[kind.text_column].is_null(),
+                text,
+                !data["media_seconds"].is_null(),
+                media_seconds,
+                chrono::Utc::now().to_rfc3339(),
+                id,
+                student_id
+            ],
+        )
+        .map_err(|e| format!("Failed to update {}: {}", kind.table, e))?;
+
+    if updated == 0 {
+        return Err(format!("{} entry {} not found", kind.table, id));
+    }
+
+    let saved = load_annotation(conn, kind, id)?;
+    queue_annotation(conn, kind, "update", id, &saved)?;
+
+    Ok(saved)
+}
+
+fn delete_annotation(conn: &Connection, kind: &AnnotationKind, id: &str) -> Result<(), String> {
+    let student_id = access::require_student_id(conn)?;
+
+    let deleted = conn
+        .execute(
+            &format!("DELETE FROM {} WHERE id = ?1 AND student_id = ?2", kind.table),
+            params![id, student_id],
+        )
+        .map_err(|e| format!("Failed to delete {}: {}", kind.table, e))?;
+
+    if deleted == 0 {
+        return Err(format!("{} entry {} not found", kind.table, id));
+    }
+
+    let tombstone = serde_json::json!({ "id": id, "student_id": student_id });
+    queue_annotation(conn, kind, "delete", id, &tombstone.to_string())
+}
+
+// ============================================================================
+// BOOKMARK COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub fn create_bookmark(db_path: String, bookmark_data: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    create_annotation(&conn, &BOOKMARK, &bookmark_data)
+}
+
+#[tauri::command]
+pub fn update_bookmark(
+    db_path: String,
+    bookmark_id: String,
+    bookmark_data: String,
+) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    update_annotation(&conn, &BOOKMARK, &bookmark_id, &bookmark_data)
+}
+
+#[tauri::command]
+pub fn delete_bookmark(db_path: String, bookmark_id: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    delete_annotation(&conn, &BOOKMARK, &bookmark_id)?;
+
+    Ok("Bookmark deleted successfully".to_string())
+}
+
+// ============================================================================
+// NOTE COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub fn create_note(db_path: String, note_data: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    create_annotation(&conn, &NOTE, &note_data)
+}
+
+#[tauri::command]
+pub fn update_note(db_path: String, note_id: String, note_data: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    update_annotation(&conn, &NOTE, &note_id, &note_data)
+}
+
+#[tauri::command]
+pub fn delete_note(db_path: String, note_id: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    delete_annotation(&conn, &NOTE, &note_id)?;
+
+    Ok("Note deleted successfully".to_string())
+}
+
+// ============================================================================
+// LISTING AND EXPORT
+// ============================================================================
+
+fn annotations_by(conn: &Connection, filter: &str, value: &str) -> Result<String, String> {
+    let student_id = access::require_student_id(conn)?;
+
+    let bookmarks = list_annotations(conn, &BOOKMARK, filter, &student_id, value)?;
+    let notes = list_annotations(conn, &NOTE, filter, &student_id, value)?;
+
+    Ok(format!(
+        r#"{{"bookmarks":[{}],"notes":[{}]}}"#,
+        bookmarks.join(","),
+        notes.join(",")
+    ))
+}
+
+#[tauri::command]
+pub fn get_course_annotations(db_path: String, course_id: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    annotations_by(&conn, "course_id", &course_id)
+}
+
+#[tauri::command]
+pub fn get_content_annotations(db_path: String, content_id: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    annotations_by(&conn, "content_id", &content_id)
+}
+
+// 75.0 → "1:15", 3725.0 → "1:02:05"
+fn format_timestamp(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    let (hours, minutes, secs) = (total / 3600, (total % 3600) / 60, total % 60);
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
+
+// Exports the learner's notes and bookmarks for a course as Markdown (the
+// default) or JSON
+#[tauri::command]
+pub fn export_course_notes(
+    db_path: String,
+    course_id: String,
+    format: Option<String>,
+) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let annotations = annotations_by(&conn, "course_id", &course_id)?;
+
+    match format.as_deref().unwrap_or("markdown") {
+        "json" => return Ok(annotations),
+        "markdown" => {}
+        other => return Err(format!("Unknown export format '{}'. Expected markdown or json", other)),
+    }
+
+    let annotations: JsonValue =
+        serde_json::from_str(&annotations).map_err(|e| format!("Invalid annotations: {}", e))?;
+
+    let course_title: String = conn
+        .query_row(
+            "SELECT title FROM courses WHERE id = ?1",
+            params![course_id],
+            |row| row.get(0),
+        )
+        .unwrap_or_else(|_| course_id.clone());
+
+    let mut markdown = format!("# {}\n", course_title);
+    let mut current_module = "";
+    let mut current_content = "";
+
+    let empty = Vec::new();
+    let notes = annotations["notes"].as_array().unwrap_or(&empty);
+    let bookmarks = annotations["bookmarks"].as_array().unwrap_or(&empty);
+
+    let timestamp = |entry: &JsonValue| {
+        entry["media_seconds"]
+            .as_f64()
+            .map(|s| format!("[{}] ", format_timestamp(s)))
+            .unwrap_or_default()
+    };
+
+    // Notes are grouped under their module and content block headings
+    for note in notes {
+        let module_title = note["module_title"].as_str().unwrap_or("Untitled module");
+        let content_title = note["content_title"].as_str().unwrap_or("Untitled content");
+
+        if current_module != module_title {
+            markdown.push_str(&format!("\n## {}\n", module_title));
+            current_module = module_title;
+            current_content = "";
+        }
+        if current_content != content_title {
+            markdown.push_str(&format!("\n### {}\n\n", content_title));
+            current_content = content_title;
+        }
+
+        markdown.push_str(&format!(
+            "- {}{}\n",
+            timestamp(note),
+            note["body"].as_str().unwrap_or("").replace('\n', "\n  ")
+        ));
+    }
+
+    if !bookmarks.is_empty() {
+        markdown.push_str("\n## Bookmarks\n\n");
+    }
+    for bookmark in bookmarks {
+        markdown.push_str(&format!(
+            "- {} / {} {}{}\n",
+            bookmark["module_title"].as_str().unwrap_or("Untitled module"),
+            bookmark["content_title"].as_str().unwrap_or("Untitled content"),
+            timestamp(bookmark),
+            bookmark["label"].as_str().unwrap_or("")
+        ));
+    }
+
+    Ok(markdown)
+}
+
+// ============================================================================
+// SYNC
+// ============================================================================
+
+fn merge_synced(conn: &Connection, kind: &AnnotationKind, rows: &[JsonValue]) -> Result<usize, String> {
+    let mut merged = 0;
+
+    for row in rows {
+        let (Some(id), Some(student_id), Some(content_id), Some(updated_at)) = (
+            row["id"].as_str(),
+            row["student_id"].as_str(),
+            row["content_id"].as_str(),
+            row["updated_at"].as_str(),
+        ) else {
+            continue;
+        };
+
+        // Missing course/module ids are filled in from the local content
+        // block; rows that cannot be placed are skipped. Newest edit wins.
+        merged += conn
+            .execute(
+                &format!(
+                    "INSERT INTO {table}
+                     (id, student_id, course_id, module_id, content_id, media_seconds, {col}, created_at, updated_at)
+                     SELECT ?1, ?2, COALESCE(?3, m.course_id), COALESCE(?4, cb.module_id), ?5, ?6, ?7, ?8, ?9
+                     FROM (SELECT 1)
+                     LEFT JOIN content_blocks cb ON cb.id = ?5
+                     LEFT JOIN modules m ON cb.module_id = m.id
+                     WHERE COALESCE(?3, m.course_id) IS NOT NULL AND COALESCE(?4, cb.module_id) IS NOT NULL
+                     ON CONFLICT(id) DO UPDATE SET
+                        media_seconds = excluded.media_seconds,
+                        {col} = excluded.{col},
+                        updated_at = excluded.updated_at
+                     WHERE datetime(excluded.updated_at) > datetime({table}.updated_at)",
+                    table = kind.table,
+                    col = kind.text_column
+                ),
+                params![
+                    id,
+                    student_id,
+                    row["course_id"].as_str(),
+                    row["module_id"].as_str(),
+                    content_id,
+                    row["media_seconds"].as_f64(),
+                    row[kind.text_column].as_str(),
+                    row["created_at"].as_str().unwrap_or(updated_at),
+                    updated_at
+                ],
+            )
+            .map_err(|e| format!("Failed to merge {}: {}", kind.table, e))?;
+    }
+
+    Ok(merged)
+}
+
+// Applies bookmarks and notes downloaded from the server. Nothing is queued,
+// since these changes already live there.
+#[tauri::command]
+pub fn save_synced_annotations(db_path: String, annotations_data: String) -> Result<String, String> {
+    let mut conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let data: JsonValue = serde_json::from_str(&annotations_data)
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let empty = Vec::new();
+    let bookmarks = merge_synced(&tx, &BOOKMARK, data["bookmarks"].as_array().unwrap_or(&empty))?;
+    let notes = merge_synced(&tx, &NOTE, data["notes"].as_array().unwrap_or(&empty))?;
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit annotations: {}", e))?;
+
+    Ok(serde_json::json!({ "bookmarks_merged": bookmarks, "notes_merged": notes }).to_string())
+}
@@ -0,0 +1,1345 @@
+use crate::commands::{access, activity, get_connection, progress, review};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+// ============================================================================
+// QUIZ GRADING
+// ============================================================================
+// Correctness and points always come from question_options and questions,
+// never from the webview, so an edited request cannot change a result.
+
+pub const ATTEMPT_EXPIRED: &str = "ATTEMPT_EXPIRED";
+
+// Answers stamped after the attempt's deadline earn nothing. `deadline` is the
+// SQL parameter holding deadline_at; answers saved before answered_at existed
+// still count.
+fn on_time_answer(deadline: &str) -> String {
+    format!(
+        "({0} IS NULL OR answered_at IS NULL OR datetime(answered_at) <= datetime({0}))",
+        deadline
+    )
+}
+
+pub const QUESTION_TYPES: &[&str] = &["single_choice", "multiple_choice", "true_false", "short_answer"];
+
+pub struct GradedAnswer {
+    pub is_correct: bool,
+    pub points_earned: f64,
+}
+
+// What the learner submitted for one question. Which field is used depends
+// on the question type.
+#[derive(Default)]
+pub struct AnswerInput {
+    pub selected_option_id: Option<String>,
+    pub selected_option_ids: Vec<String>,
+    pub text_answer: Option<String>,
+}
+
+impl AnswerInput {
+    pub fn from_json(answer: &JsonValue) -> Self {
+        let mut selected_option_ids: Vec<String> = answer["selected_option_ids"]
+            .as_array()
+            .map(|ids| {
+                ids.iter()
+                    .filter_map(|id| id.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        selected_option_ids.sort();
+        selected_option_ids.dedup();
+
+        AnswerInput {
+            selected_option_id: answer["selected_option_id"].as_str().map(|s| s.to_string()),
+            selected_option_ids,
+            text_answer: answer["text_answer"].as_str().map(|s| s.to_string()),
+        }
+    }
+
+    // selected_option_ids as stored in quiz_answers
+    pub fn selected_option_ids_json(&self) -> Option<String> {
+        if self.selected_option_ids.is_empty() {
+            None
+        } else {
+            serde_json::to_string(&self.selected_option_ids).ok()
+        }
+    }
+}
+
+// Case, surrounding and repeated whitespace are ignored for short answers
+fn normalize_text_answer(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+pub struct AttemptGrade {
+    pub attempt_id: String,
+    pub quiz_id: String,
+    pub total_questions: i64,
+    pub answered_questions: i64,
+    pub correct_answers: i64,
+    pub late_answers: i64,
+    pub points_earned: f64,
+    pub points_possible: f64,
+    pub percentage: f64,
+    pub pass_mark_percentage: f64,
+    pub passed: bool,
+}
+
+impl AttemptGrade {
+    pub fn to_json(&self) -> JsonValue {
+        serde_json::json!({
+            "attempt_id": self.attempt_id,
+            "quiz_id": self.quiz_id,
+            "total_questions": self.total_questions,
+            "answered_questions": self.answered_questions,
+            "correct_answers": self.correct_answers,
+            "late_answers": self.late_answers,
+            "points_earned": self.points_earned,
+            "points_possible": self.points_possible,
+            "percentage": self.percentage,
+            "pass_mark_percentage": self.pass_mark_percentage,
+            "passed": self.passed
+        })
+    }
+}
+
+fn get_attempt_quiz_id(conn: &Connection, attempt_id: &str) -> Result<String, String> {
+    conn.query_row(
+        "SELECT quiz_id FROM quiz_attempts WHERE id = ?1",
+        params![attempt_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Attempt not found: {}", e))
+}
+
+// Grades one answer against the question's key. Fails if the question is
+// not part of the quiz or the answer names options from another question.
+pub fn grade_question(
+    conn: &Connection,
+    quiz_id: &str,
+    question_id: &str,
+    input: &AnswerInput,
+) -> Result<GradedAnswer, String> {
+    let (question_type, points, accepted_answers): (String, f64, Option<String>) = conn
+        .query_row(
+            "SELECT COALESCE(question_type, 'single_choice'), COALESCE(points, 1.0), accepted_answers
+             FROM questions WHERE id = ?1 AND quiz_id = ?2",
+            params![question_id, quiz_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|_| format!("Question {} does not belong to quiz {}", question_id, quiz_id))?;
+
+    let mut stmt = conn
+        .prepare("SELECT id, option_text, COALESCE(is_correct, 0) FROM question_options WHERE question_id = ?1")
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let options: Vec<(String, String, bool)> = stmt
+        .query_map(params![question_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let find_option = |option_id: &str| {
+        options
+            .iter()
+            .find(|(id, _, _)| id == option_id)
+            .ok_or_else(|| format!("Option {} does not belong to question {}", option_id, question_id))
+    };
+
+    let (is_correct, credit) = match question_type.as_str() {
+        "multiple_choice" => {
+            if input.selected_option_ids.is_empty() {
+                return Err(format!("Question {} expects selected_option_ids", question_id));
+            }
+
+            let mut right = 0usize;
+            let mut wrong = 0usize;
+            for option_id in &input.selected_option_ids {
+                if find_option(option_id)?.2 {
+                    right += 1;
+                } else {
+                    wrong += 1;
+                }
+            }
+
+            // Each wrong pick cancels a right one, so selecting everything earns nothing
+            let total_correct = options.iter().filter(|(_, _, c)| *c).count();
+            let credit = if total_correct > 0 {
+                right.saturating_sub(wrong) as f64 / total_correct as f64
+            } else {
+                0.0
+            };
+
+            (wrong == 0 && right == total_correct, credit)
+        }
+        "short_answer" => {
+            let text = input
+                .text_answer
+                .as_deref()
+                .map(normalize_text_answer)
+                .filter(|t| !t.is_empty())
+                .ok_or_else(|| format!("Question {} expects a text_answer", question_id))?;
+
+            // Correct options double as accepted variants
+            let accepted: Vec<String> = accepted_answers
+                .as_deref()
+                .and_then(|json| serde_json::from_str::<Vec<String>>(json).ok())
+                .unwrap_or_default()
+                .into_iter()
+                .chain(
+                    options
+                        .iter()
+                        .filter(|(_, _, c)| *c)
+                        .map(|(_, text, _)| text.clone()),
+                )
+                .collect();
+
+            let is_correct = accepted.iter().any(|a| normalize_text_answer(a) == text);
+            (is_correct, if is_correct { 1.0 } else { 0.0 })
+        }
+        _ => {
+            let option_id = input
+                .selected_option_id
+                .as_deref()
+                .ok_or_else(|| format!("Question {} expects a selected_option_id", question_id))?;
+
+            let is_correct = find_option(option_id)?.2;
+            (is_correct, if is_correct { 1.0 } else { 0.0 })
+        }
+    };
+
+    Ok(GradedAnswer {
+        is_correct,
+        points_earned: (points * credit * 100.0).round() / 100.0,
+    })
+}
+
+pub fn grade_answer(
+    conn: &Connection,
+    attempt_id: &str,
+    question_id: &str,
+    input: &AnswerInput,
+) -> Result<GradedAnswer, String> {
+    let quiz_id = get_attempt_quiz_id(conn, attempt_id)?;
+    grade_question(conn, &quiz_id, question_id, input)
+}
+
+// Re-grades every stored answer against the current answer key and totals
+// the attempt. Nothing is written to quiz_attempts.
+pub fn grade_attempt(conn: &Connection, attempt_id: &str) -> Result<AttemptGrade, String> {
+    let (quiz_id, deadline_at): (String, Option<String>) = conn
+        .query_row(
+            "SELECT quiz_id, deadline_at FROM quiz_attempts WHERE id = ?1",
+            params![attempt_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Attempt not found: {}", e))?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id, question_id, selected_option_id, selected_option_ids, text_answer, {}
+             FROM quiz_answers WHERE attempt_id = ?1",
+            on_time_answer("?2")
+        ))
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let answers: Vec<(String, String, AnswerInput, bool)> = stmt
+        .query_map(params![attempt_id, deadline_at], |row| {
+            let selected_option_ids: Option<String> = row.get(3)?;
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                AnswerInput {
+                    selected_option_id: row.get(2)?,
+                    selected_option_ids: selected_option_ids
+                        .and_then(|json| serde_json::from_str(&json).ok())
+                        .unwrap_or_default(),
+                    text_answer: row.get(4)?,
+                },
+                row.get(5)?,
+            ))
+        })
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    for (answer_id, question_id, input, on_time) in answers {
+        // An answer the current key can no longer match (e.g. a removed option) earns nothing
+        let graded = grade_question(conn, &quiz_id, &question_id, &input).unwrap_or(GradedAnswer {
+            is_correct: false,
+            points_earned: 0.0,
+        });
+
+        conn.execute(
+            "UPDATE quiz_answers SET is_correct = ?1, points_earned = ?2 WHERE id = ?3",
+            params![
+                graded.is_correct,
+                if on_time { graded.points_earned } else { 0.0 },
+                answer_id
+            ],
+        )
+        .map_err(|e| format!("Failed to grade answers: {}", e))?;
+    }
+
+    total_attempt(conn, attempt_id, quiz_id, deadline_at)
+}
+
+// The grade recorded when the attempt was submitted. Answers are not re-graded.
+fn stored_grade(conn: &Connection, attempt_id: &str) -> Result<AttemptGrade, String> {
+    let (quiz_id, deadline_at, score, passed): (String, Option<String>, Option<f64>, Option<bool>) =
+        conn.query_row(
+            "SELECT quiz_id, deadline_at, score, passed FROM quiz_attempts WHERE id = ?1",
+            params![attempt_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|e| format!("Attempt not found: {}", e))?;
+
+    let mut grade = total_attempt(conn, attempt_id, quiz_id, deadline_at)?;
+    if let Some(score) = score {
+        grade.percentage = score;
+    }
+    if let Some(passed) = passed {
+        grade.passed = passed;
+    }
+
+    Ok(grade)
+}
+
+// Totals the graded answers stored for the attempt
+fn total_attempt(
+    conn: &Connection,
+    attempt_id: &str,
+    quiz_id: String,
+    deadline_at: Option<String>,
+) -> Result<AttemptGrade, String> {
+    let (total_questions, points_possible, pass_mark_percentage): (i64, f64, f64) = conn
+        .query_row(
+            "SELECT
+                (SELECT COUNT(*) FROM questions WHERE quiz_id = ?1),
+                (SELECT COALESCE(SUM(COALESCE(points, 1.0)), 0.0) FROM questions WHERE quiz_id = ?1),
+                pass_mark_percentage
+             FROM quizzes WHERE id = ?1",
+            params![quiz_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| format!("Quiz not found: {}", e))?;
+
+    let (answered_questions, correct_answers, late_answers, points_earned): (i64, i64, i64, f64) =
+        conn.query_row(
+            &format!(
+                "SELECT
+                    COUNT(CASE WHEN {0} THEN 1 END),
+                    COUNT(CASE WHEN {0} AND is_correct = 1 THEN 1 END),
+                    COUNT(CASE WHEN NOT {0} THEN 1 END),
+                    COALESCE(SUM(points_earned), 0.0)
+                 FROM quiz_answers
+                 JOIN questions q ON quiz_answers.question_id = q.id
+                 WHERE quiz_answers.attempt_id = ?1 AND q.quiz_id = ?2",
+                on_time_answer("?3")
+            ),
+            params![attempt_id, quiz_id, deadline_at],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|e| format!("Failed to calculate score: {}", e))?;
+
+    let percentage = if points_possible > 0.0 {
+        (points_earned / points_possible * 10000.0).round() / 100.0
+    } else {
+        0.0
+    };
+
+    Ok(AttemptGrade {
+        attempt_id: attempt_id.to_string(),
+        quiz_id,
+        total_questions,
+        answered_questions,
+        correct_answers,
+        late_answers,
+        points_earned,
+        points_possible,
+        percentage,
+        pass_mark_percentage,
+        passed: percentage >= pass_mark_percentage,
+    })
+}
+
+// Grades the attempt and records the result as completed. An attempt that
+// was already submitted keeps its recorded grade.
+pub fn finalize_attempt(conn: &Connection, attempt_id: &str) -> Result<AttemptGrade, String> {
+    let status: Option<String> = conn
+        .query_row(
+            "SELECT status FROM quiz_attempts WHERE id = ?1",
+            params![attempt_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Attempt not found: {}", e))?;
+
+    match status.as_deref() {
+        Some("in_progress") | None => {}
+        Some("abandoned") => {
+            return Err(format!("Attempt {} was abandoned and cannot be graded", attempt_id))
+        }
+        Some(_) => return stored_grade(conn, attempt_id),
+    }
+
+    let grade = grade_attempt(conn, attempt_id)?;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    // An overdue attempt is recorded as completed at its deadline
+    let updated = conn.execute(
+        "UPDATE quiz_attempts
+         SET status = 'completed',
+             completed_at = COALESCE(completed_at, CASE
+                WHEN deadline_at IS NOT NULL AND datetime(deadline_at) < datetime(?1) THEN deadline_at
+                ELSE ?1
+             END),
+             score = ?2, passed = ?3, updated_at = ?1, last_synced_at = datetime('now')
+         WHERE id = ?4 AND COALESCE(status, 'in_progress') = 'in_progress'",
+        params![now, grade.percentage, grade.passed, attempt_id],
+    )
+    .map_err(|e| format!("Failed to update attempt: {}", e))?;
+
+    // Submitted by another call in the meantime
+    if updated == 0 {
+        return stored_grade(conn, attempt_id);
+    }
+
+    activity::log_quiz_submission(conn, attempt_id)?;
+    review::seed_from_attempt(conn, attempt_id)?;
+
+    // Passing the final exam is usually the last step of a course
+    if grade.passed {
+        progress::evaluate_completion_for_attempt(conn, attempt_id)?;
+    }
+
+    Ok(grade)
+}
+
+// ============================================================================
+// ATTEMPT DEADLINES
+// ============================================================================
+
+// started_at plus the quiz's time limit, or None for untimed quizzes
+pub fn compute_deadline(
+    conn: &Connection,
+    quiz_id: &str,
+    started_at: &str,
+) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT CASE WHEN time_limit_minutes > 0
+                THEN strftime('%Y-%m-%dT%H:%M:%SZ', ?2, '+' || time_limit_minutes || ' minutes')
+             END
+         FROM quizzes WHERE id = ?1",
+        params![quiz_id, started_at],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Quiz not found: {}", e))
+}
+
+pub fn is_overdue(conn: &Connection, attempt_id: &str) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT deadline_at IS NOT NULL AND datetime(deadline_at) < datetime('now')
+         FROM quiz_attempts WHERE id = ?1",
+        params![attempt_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Attempt not found: {}", e))
+}
+
+pub fn attempt_expired_error(attempt_id: &str) -> String {
+    format!(
+        "{}: The time limit for attempt {} has passed and it has been submitted.",
+        ATTEMPT_EXPIRED, attempt_id
+    )
+}
+
+// Submits every in-progress attempt whose deadline has passed. Runs at
+// startup and before attempts are read or resumed. One broken attempt does
+// not hold up the rest; returns (submitted, failed).
+pub fn finalize_overdue_attempts(conn: &Connection) -> Result<(usize, usize), String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id FROM quiz_attempts
+             WHERE status = 'in_progress'
+               AND deadline_at IS NOT NULL
+               AND datetime(deadline_at) < datetime('now')",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let overdue: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut submitted = 0;
+    let mut failed = 0;
+    for attempt_id in &overdue {
+        // Each attempt commits or rolls back on its own
+        conn.execute_batch("SAVEPOINT finalize_overdue;")
+            .map_err(|e| format!("Failed to start savepoint: {}", e))?;
+
+        match finalize_attempt(conn, attempt_id) {
+            Ok(grade) => {
+                conn.execute_batch("RELEASE finalize_overdue;")
+                    .map_err(|e| format!("Failed to release savepoint: {}", e))?;
+                submitted += 1;
+                println!(
+                    "⏰ Auto-submitted overdue attempt {}: {}%",
+                    attempt_id, grade.percentage
+                );
+            }
+            Err(e) => {
+                conn.execute_batch("ROLLBACK TO finalize_overdue; RELEASE finalize_overdue;")
+                    .map_err(|e| format!("Failed to roll back savepoint: {}", e))?;
+                failed += 1;
+                log::warn!("Failed to auto-submit overdue attempt {}: {}", attempt_id, e);
+            }
+        }
+    }
+
+    Ok((submitted, failed))
+}
+
+// ============================================================================
+// QUESTION ORDER
+// ============================================================================
+// Shuffles are driven by the attempt's shuffle_seed (splitmix64 feeding a
+// Fisher-Yates shuffle) and the result is stored in attempt_question_order.
+
+fn next_random(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn shuffle<T>(items: &mut [T], state: &mut u64) {
+    for i in (1..items.len()).rev() {
+        let j = (next_random(state) % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+pub fn generate_shuffle_seed() -> Result<i64, String> {
+    let mut bytes = [0u8; 8];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("Failed to generate seed: {}", e))?;
+    Ok(i64::from_le_bytes(bytes))
+}
+
+fn query_ids(conn: &Connection, sql: &str, id: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let ids: Vec<String> = stmt
+        .query_map(params![id], |row| row.get(0))
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(ids)
+}
+
+// Persists the attempt's question and option order the first time it is
+// needed. Attempts synced from the server without a seed get one here.
+pub fn ensure_attempt_order(conn: &Connection, attempt_id: &str) -> Result<(), String> {
+    let already_ordered: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM attempt_question_order WHERE attempt_id = ?1)",
+            params![attempt_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to read question order: {}", e))?;
+
+    if already_ordered {
+        return Ok(());
+    }
+
+    let (quiz_id, shuffle_seed, shuffle_questions, shuffle_options): (
+        String,
+        Option<i64>,
+        bool,
+        bool,
+    ) = conn
+        .query_row(
+            "SELECT a.quiz_id, a.shuffle_seed,
+                    COALESCE(q.shuffle_questions, 0), COALESCE(q.shuffle_options, 0)
+             FROM quiz_attempts a
+             JOIN quizzes q ON a.quiz_id = q.id
+             WHERE a.id = ?1",
+            params![attempt_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|e| format!("Attempt not found: {}", e))?;
+
+    let mut questions = query_ids(
+        conn,
+        "SELECT id FROM questions WHERE quiz_id = ?1 ORDER BY order_index ASC, id ASC",
+        &quiz_id,
+    )?;
+
+    // Questions not downloaded yet - order them once they are
+    if questions.is_empty() {
+        return Ok(());
+    }
+
+    let seed = match shuffle_seed {
+        Some(seed) => seed,
+        None => {
+            let seed = generate_shuffle_seed()?;
+            conn.execute(
+                "UPDATE quiz_attempts SET shuffle_seed = ?1 WHERE id = ?2",
+                params![seed, attempt_id],
+            )
+            .map_err(|e| format!("Failed to save shuffle seed: {}", e))?;
+            seed
+        }
+    };
+
+    let mut state = seed as u64;
+    if shuffle_questions {
+        shuffle(&mut questions, &mut state);
+    }
+
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    for (position, question_id) in questions.iter().enumerate() {
+        let mut options = query_ids(
+            &tx,
+            "SELECT id FROM question_options WHERE question_id = ?1 ORDER BY order_index ASC, id ASC",
+            question_id,
+        )?;
+
+        if shuffle_options {
+            shuffle(&mut options, &mut state);
+        }
+
+        tx.execute(
+            "INSERT INTO attempt_question_order (attempt_id, question_id, position, option_order)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                attempt_id,
+                question_id,
+                position as i64,
+                serde_json::to_string(&options).unwrap_or_else(|_| "[]".to_string())
+            ],
+        )
+        .map_err(|e| format!("Failed to save question order: {}", e))?;
+    }
+
+    tx.commit()
+        .map_err(|e| format!("Failed to save question order: {}", e))?;
+
+    Ok(())
+}
+
+// ============================================================================
+// GRADING COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub fn grade_quiz_attempt(db_path: String, attempt_id: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    access::ensure_own_attempt(&conn, &attempt_id)?;
+
+    let grade = finalize_attempt(&conn, &attempt_id)?;
+
+    println!(
+        "📝 Graded attempt {}: {}% (pass mark {}%)",
+        attempt_id, grade.percentage, grade.pass_mark_percentage
+    );
+
+    Ok(grade.to_json().to_string())
+}
+
+// ============================================================================
+// ATTEMPT WINDOW
+// ============================================================================
+// max_attempts is counted over a rolling window of attempt_reset_hours
+// (0 means attempts never reset). A NULL max_attempts means unlimited.
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "code", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AttemptError {
+    QuizNotFound {
+        quiz_id: String,
+    },
+    AccessDenied {
+        message: String,
+    },
+    AttemptsExhausted {
+        max_attempts: i64,
+        attempts_used: i64,
+        // None when the quiz never resets
+        resets_at: Option<String>,
+    },
+    Database {
+        message: String,
+    },
+}
+
+impl From<String> for AttemptError {
+    fn from(message: String) -> Self {
+        AttemptError::Database { message }
+    }
+}
+
+pub struct AttemptWindow {
+    pub max_attempts: Option<i64>,
+    pub attempt_reset_hours: i64,
+    pub attempts_used: i64,
+    pub resets_at: Option<String>,
+}
+
+impl AttemptWindow {
+    pub fn attempts_remaining(&self) -> Option<i64> {
+        self.max_attempts
+            .map(|max| (max - self.attempts_used).max(0))
+    }
+}
+
+pub fn get_attempt_window(
+    conn: &Connection,
+    quiz_id: &str,
+    student_id: &str,
+) -> Result<AttemptWindow, AttemptError> {
+    let (max_attempts, attempt_reset_hours): (Option<i64>, i64) = conn
+        .query_row(
+            "SELECT max_attempts, COALESCE(attempt_reset_hours, 0) FROM quizzes WHERE id = ?1",
+            params![quiz_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|_| AttemptError::QuizNotFound {
+            quiz_id: quiz_id.to_string(),
+        })?;
+
+    // Only attempts started inside the window count; the window reopens when
+    // the oldest of them falls out of it
+    let window_start = if attempt_reset_hours > 0 {
+        format!("-{} hours", attempt_reset_hours)
+    } else {
+        "-1000 years".to_string()
+    };
+
+    let (attempts_used, oldest_started_at): (i64, Option<String>) = conn
+        .query_row(
+            "SELECT COUNT(*), MIN(datetime(started_at)) FROM quiz_attempts
+             WHERE quiz_id = ?1 AND student_id = ?2
+               AND datetime(started_at) > datetime('now', ?3)",
+            params![quiz_id, student_id, window_start],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Failed to count attempts: {}", e))?;
+
+    let resets_at = match (attempt_reset_hours > 0, oldest_started_at) {
+        (true, Some(oldest)) => conn
+            .query_row(
+                "SELECT strftime('%Y-%m-%dT%H:%M:%SZ', ?1, ?2)",
+                params![oldest, format!("+{} hours", attempt_reset_hours)],
+                |row| row.get(0),
+            )
+            .ok(),
+        _ => None,
+    };
+
+    Ok(AttemptWindow {
+        max_attempts,
+        attempt_reset_hours,
+        attempts_used,
+        resets_at,
+    })
+}
+
+// Attempts are only opened by the signed-in learner, in a course they are
+// enrolled in
+pub fn ensure_can_attempt(
+    conn: &Connection,
+    quiz_id: &str,
+    student_id: &str,
+) -> Result<(), AttemptError> {
+    access::ensure_current_student(conn, student_id)
+        .and_then(|_| access::require_quiz_enrollment_id(conn, quiz_id))
+        .and_then(|enrollment_id| access::ensure_own_enrollment(conn, &enrollment_id))
+        .and_then(|_| access::ensure_quiz_access(conn, quiz_id))
+        .map_err(|message| AttemptError::AccessDenied { message })
+}
+
+// The number a new attempt gets, or AttemptsExhausted when the window is full
+pub fn next_attempt_number(
+    conn: &Connection,
+    quiz_id: &str,
+    student_id: &str,
+    window: AttemptWindow,
+) -> Result<i64, AttemptError> {
+    if let Some(max_attempts) = window.max_attempts {
+        if window.attempts_used >= max_attempts {
+            return Err(AttemptError::AttemptsExhausted {
+                max_attempts,
+                attempts_used: window.attempts_used,
+                resets_at: window.resets_at,
+            });
+        }
+    }
+
+    let attempt_number = conn
+        .query_row(
+            "SELECT COALESCE(MAX(attempt_number), 0) + 1 FROM quiz_attempts
+             WHERE quiz_id = ?1 AND student_id = ?2",
+            params![quiz_id, student_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to number attempt: {}", e))?;
+
+    Ok(attempt_number)
+}
+
+fn attempt_json(conn: &Connection, attempt_id: &str) -> Result<JsonValue, String> {
+    let json: String = conn
+        .query_row(
+            "SELECT json_object(
+                'id', id,
+                'student_id', student_id,
+                'quiz_id', quiz_id,
+                'attempt_number', attempt_number,
+                'status', status,
+                'started_at', started_at,
+                'completed_at', completed_at,
+                'score', score,
+                'passed', passed,
+                'deadline_at', deadline_at,
+                'time_remaining_seconds', CASE
+                    WHEN deadline_at IS NOT NULL AND status = 'in_progress'
+                    THEN MAX(0, CAST((julianday(deadline_at) - julianday('now')) * 86400 AS INTEGER))
+                    ELSE time_remaining_seconds
+                END,
+                'created_at', created_at,
+                'updated_at', updated_at
+             ) FROM quiz_attempts WHERE id = ?1",
+            params![attempt_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Attempt not found: {}", e))?;
+
+    serde_json::from_str(&json).map_err(|e| format!("Invalid attempt JSON: {}", e))
+}
+
+// ============================================================================
+// ATTEMPT COMMANDS
+// ============================================================================
+
+// Resumes the learner's in-progress attempt if there is one, otherwise opens
+// the next attempt when the window allows it
+#[tauri::command]
+pub fn start_quiz_attempt(
+    db_path: String,
+    quiz_id: String,
+    student_id: String,
+) -> Result<String, AttemptError> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let window = get_attempt_window(&conn, &quiz_id, &student_id)?;
+    ensure_can_attempt(&conn, &quiz_id, &student_id)?;
+
+    // An attempt whose timer has run out is submitted rather than resumed
+    finalize_overdue_attempts(&conn)?;
+
+    let in_progress: Option<String> = conn
+        .query_row(
+            "SELECT id FROM quiz_attempts
+             WHERE quiz_id = ?1 AND student_id = ?2 AND status = 'in_progress'
+             ORDER BY attempt_number DESC
+             LIMIT 1",
+            params![quiz_id, student_id],
+            |row| row.get(0),
+        )
+        .ok();
+
+    let (attempt_id, resumed, window) = match in_progress {
+        Some(attempt_id) => (attempt_id, true, window),
+        None => {
+            let attempt_number = next_attempt_number(&conn, &quiz_id, &student_id, window)?;
+
+            let attempt_id = uuid::Uuid::new_v4().to_string();
+            let now = chrono::Utc::now().to_rfc3339();
+            let deadline_at = compute_deadline(&conn, &quiz_id, &now)?;
+            let shuffle_seed = generate_shuffle_seed()?;
+
+            conn.execute(
+                "INSERT INTO quiz_attempts
+                 (id, student_id, quiz_id, attempt_number, status, started_at, deadline_at,
+                  shuffle_seed, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, 'in_progress', ?5, ?6, ?7, ?5, ?5)",
+                params![
+                    attempt_id,
+                    student_id,
+                    quiz_id,
+                    attempt_number,
+                    now,
+                    deadline_at,
+                    shuffle_seed
+                ],
+            )
+            .map_err(|e| format!("Failed to start quiz attempt: {}", e))?;
+
+            ensure_attempt_order(&conn, &attempt_id)?;
+
+            println!("📝 Started attempt {} for quiz {}", attempt_number, quiz_id);
+
+            // Re-read so the new attempt counts against the window
+            let window = get_attempt_window(&conn, &quiz_id, &student_id)?;
+            (attempt_id, false, window)
+        }
+    };
+
+    let result = serde_json::json!({
+        "attempt": attempt_json(&conn, &attempt_id)?,
+        "resumed": resumed,
+        "max_attempts": window.max_attempts,
+        "attempts_used": window.attempts_used,
+        "attempts_remaining": window.attempts_remaining(),
+        "attempt_reset_hours": window.attempt_reset_hours,
+        "resets_at": window.resets_at
+    });
+
+    Ok(result.to_string())
+}
+
+// Questions in the order the attempt presents them. Correctness is only
+// included when `reveal` is set, which callers limit to completed attempts.
+fn load_attempt_questions(
+    conn: &Connection,
+    attempt_id: &str,
+    quiz_id: &str,
+    reveal: bool,
+) -> Result<Vec<JsonValue>, String> {
+    ensure_attempt_order(conn, attempt_id)?;
+
+    // Questions added after the order was fixed go last, in their own order
+    let mut stmt = conn
+        .prepare(
+            "SELECT q.id, q.question_text, q.image_url, q.points, ao.option_order,
+                    qa.selected_option_id, qa.is_correct, qa.points_earned,
+                    COALESCE(q.question_type, 'single_choice'), q.accepted_answers,
+                    qa.selected_option_ids, qa.text_answer
+             FROM questions q
+             LEFT JOIN attempt_question_order ao
+                ON ao.question_id = q.id AND ao.attempt_id = ?1
+             LEFT JOIN quiz_answers qa
+                ON qa.question_id = q.id AND qa.attempt_id = ?1
+             WHERE q.quiz_id = ?2
+             ORDER BY ao.position IS NULL, ao.position ASC, q.order_index ASC, q.id ASC",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let rows: Vec<AttemptQuestionRow> = stmt
+        .query_map(params![attempt_id, quiz_id], |row| {
+            Ok(AttemptQuestionRow {
+                id: row.get(0)?,
+                question_text: row.get(1)?,
+                image_url: row.get(2)?,
+                points: row.get(3)?,
+                option_order: row.get(4)?,
+                selected_option_id: row.get(5)?,
+                answer_is_correct: row.get(6)?,
+                points_earned: row.get(7)?,
+                question_type: row.get(8)?,
+                accepted_answers: row.get(9)?,
+                selected_option_ids: row.get(10)?,
+                text_answer: row.get(11)?,
+            })
+        })
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut option_stmt = conn
+        .prepare(
+            "SELECT id, option_text, COALESCE(is_correct, 0) FROM question_options
+             WHERE question_id = ?1
+             ORDER BY order_index ASC, id ASC",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let mut questions = Vec::new();
+    for (position, row) in rows.into_iter().enumerate() {
+        let mut options: Vec<(String, String, bool)> = option_stmt
+            .query_map(params![row.id], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+            .map_err(|e| format!("Query failed: {}", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        // Options missing from the stored order keep their relative order at the end
+        let option_order: Vec<String> = row
+            .option_order
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default();
+        options.sort_by_key(|(id, _, _)| {
+            option_order
+                .iter()
+                .position(|o| o == id)
+                .unwrap_or(usize::MAX)
+        });
+
+        // A short-answer question's correct options are its accepted answers
+        if !reveal && row.question_type == "short_answer" {
+            options.clear();
+        }
+
+        let correct_option_ids: Vec<String> = options
+            .iter()
+            .filter(|(_, _, is_correct)| *is_correct)
+            .map(|(id, _, _)| id.clone())
+            .collect();
+
+        let options: Vec<JsonValue> = options
+            .into_iter()
+            .enumerate()
+            .map(|(i, (id, option_text, is_correct))| {
+                let mut option = serde_json::json!({
+                    "id": id,
+                    "question_id": row.id,
+                    "option_text": option_text,
+                    "order": i + 1
+                });
+                if reveal {
+                    option["is_correct"] = JsonValue::Bool(is_correct);
+                }
+                option
+            })
+            .collect();
+
+        let mut question = serde_json::json!({
+            "id": row.id,
+            "quiz_id": quiz_id,
+            "question_text": row.question_text,
+            "question_type": row.question_type,
+            "image_url": row.image_url,
+            "order": position + 1,
+            "points": row.points,
+            "selected_option_id": row.selected_option_id,
+            "selected_option_ids": parse_json_array(row.selected_option_ids.as_deref()),
+            "text_answer": row.text_answer,
+            "options": options
+        });
+
+        if reveal {
+            question["correct_option_id"] = serde_json::json!(correct_option_ids.first());
+            question["correct_option_ids"] = serde_json::json!(correct_option_ids);
+            question["accepted_answers"] = parse_json_array(row.accepted_answers.as_deref());
+            question["is_correct"] = JsonValue::Bool(row.answer_is_correct.unwrap_or(false));
+            question["points_earned"] = serde_json::json!(row.points_earned.unwrap_or(0.0));
+        }
+
+        questions.push(question);
+    }
+
+    Ok(questions)
+}
+
+struct AttemptQuestionRow {
+    id: String,
+    question_text: String,
+    image_url: Option<String>,
+    points: Option<f64>,
+    option_order: Option<String>,
+    selected_option_id: Option<String>,
+    answer_is_correct: Option<bool>,
+    points_earned: Option<f64>,
+    question_type: String,
+    accepted_answers: Option<String>,
+    selected_option_ids: Option<String>,
+    text_answer: Option<String>,
+}
+
+fn parse_json_array(json: Option<&str>) -> JsonValue {
+    json.and_then(|j| serde_json::from_str(j).ok())
+        .unwrap_or_else(|| JsonValue::Array(Vec::new()))
+}
+
+// Learner-facing questions for an attempt: never includes correct answers
+#[tauri::command]
+pub fn get_attempt_questions(db_path: String, attempt_id: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    access::ensure_own_attempt(&conn, &attempt_id)?;
+
+    let quiz_id = get_attempt_quiz_id(&conn, &attempt_id)?;
+    access::ensure_quiz_access(&conn, &quiz_id)?;
+
+    let questions = load_attempt_questions(&conn, &attempt_id, &quiz_id, false)?;
+
+    Ok(JsonValue::Array(questions).to_string())
+}
+
+// Review of a finished attempt. Correct answers are included only when the
+// quiz's show_correct_answers setting allows it.
+#[tauri::command]
+pub fn get_attempt_review(db_path: String, attempt_id: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    finalize_overdue_attempts(&conn)?;
+
+    let (quiz_id, status, show_correct_answers): (String, Option<String>, bool) = conn
+        .query_row(
+            "SELECT a.quiz_id, a.status, COALESCE(q.show_correct_answers, 1)
+             FROM quiz_attempts a
+             JOIN quizzes q ON a.quiz_id = q.id
+             WHERE a.id = ?1",
+            params![attempt_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| format!("Attempt not found: {}", e))?;
+
+    access::ensure_own_attempt(&conn, &attempt_id)?;
+    access::ensure_quiz_access(&conn, &quiz_id)?;
+
+    if status.as_deref() != Some("completed") {
+        return Err(format!(
+            "Attempt {} has not been completed and cannot be reviewed",
+            attempt_id
+        ));
+    }
+
+    let questions = load_attempt_questions(&conn, &attempt_id, &quiz_id, show_correct_answers)?;
+
+    let review = serde_json::json!({
+        "attempt": attempt_json(&conn, &attempt_id)?,
+        "correct_answers_visible": show_correct_answers,
+        "questions": questions
+    });
+
+    Ok(review.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Just the columns grade_question reads
+    fn answer_key() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE questions (id TEXT PRIMARY KEY, quiz_id TEXT, question_type TEXT,
+                                     points REAL, accepted_answers TEXT);
+             CREATE TABLE question_options (id TEXT PRIMARY KEY, question_id TEXT,
+                                            option_text TEXT, is_correct BOOLEAN);
+             INSERT INTO questions VALUES
+                 ('single', 'q1', NULL, NULL, NULL),
+                 ('multi', 'q1', 'multiple_choice', 4.0, NULL),
+                 ('tf', 'q1', 'true_false', 2.0, NULL),
+                 ('short', 'q1', 'short_answer', 1.0, '[\"Mitochondria\", \"the mitochondrion\"]');
+             INSERT INTO question_options VALUES
+                 ('s1', 'single', 'Right', 1), ('s2', 'single', 'Wrong', 0),
+                 ('m1', 'multi', 'A', 1), ('m2', 'multi', 'B', 1),
+                 ('m3', 'multi', 'C', 1), ('m4', 'multi', 'D', 0),
+                 ('m5', 'multi', 'E', 0), ('m6', 'multi', 'F', 0),
+                 ('t1', 'tf', 'True', 0), ('t2', 'tf', 'False', 1),
+                 ('x1', 'short', 'Powerhouse  of the CELL', 1);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn pick(ids: &[&str]) -> AnswerInput {
+        AnswerInput {
+            selected_option_ids: ids.iter().map(|id| id.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn choose(id: &str) -> AnswerInput {
+        AnswerInput {
+            selected_option_id: Some(id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn write(text: &str) -> AnswerInput {
+        AnswerInput {
+            text_answer: Some(text.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn grade(conn: &Connection, question_id: &str, input: &AnswerInput) -> (bool, f64) {
+        let graded = grade_question(conn, "q1", question_id, input).unwrap();
+        (graded.is_correct, graded.points_earned)
+    }
+
+    #[test]
+    fn single_choice_defaults_to_one_point() {
+        let conn = answer_key();
+        assert_eq!(grade(&conn, "single", &choose("s1")), (true, 1.0));
+        assert_eq!(grade(&conn, "single", &choose("s2")), (false, 0.0));
+    }
+
+    #[test]
+    fn multiple_choice_earns_partial_credit() {
+        let conn = answer_key();
+        assert_eq!(grade(&conn, "multi", &pick(&["m1", "m2", "m3"])), (true, 4.0));
+        assert_eq!(grade(&conn, "multi", &pick(&["m1", "m2"])), (false, 2.67));
+        assert_eq!(grade(&conn, "multi", &pick(&["m1", "m2", "m4"])), (false, 1.33));
+        let everything = pick(&["m1", "m2", "m3", "m4", "m5", "m6"]);
+        assert_eq!(grade(&conn, "multi", &everything), (false, 0.0));
+    }
+
+    #[test]
+    fn more_wrong_than_right_picks_earn_nothing() {
+        let conn = answer_key();
+        assert_eq!(grade(&conn, "multi", &pick(&["m1", "m4", "m5"])), (false, 0.0));
+        assert_eq!(grade(&conn, "multi", &pick(&["m4", "m5", "m6"])), (false, 0.0));
+    }
+
+    #[test]
+    fn true_false_uses_its_options() {
+        let conn = answer_key();
+        assert_eq!(grade(&conn, "tf", &choose("t2")), (true, 2.0));
+        assert_eq!(grade(&conn, "tf", &choose("t1")), (false, 0.0));
+    }
+
+    #[test]
+    fn short_answers_ignore_case_and_spacing() {
+        let conn = answer_key();
+        assert_eq!(grade(&conn, "short", &write("  mitochondria ")), (true, 1.0));
+        assert_eq!(grade(&conn, "short", &write("The   Mitochondrion")), (true, 1.0));
+        assert_eq!(grade(&conn, "short", &write("powerhouse of the cell")), (true, 1.0));
+        assert_eq!(grade(&conn, "short", &write("nucleus")), (false, 0.0));
+        assert!(grade_question(&conn, "q1", "short", &write("   ")).is_err());
+    }
+
+    #[test]
+    fn answers_must_fit_the_question() {
+        let conn = answer_key();
+        assert!(grade_question(&conn, "q2", "single", &choose("s1")).is_err());
+        assert!(grade_question(&conn, "q1", "single", &choose("m1")).is_err());
+        assert!(grade_question(&conn, "q1", "multi", &pick(&[])).is_err());
+        assert!(grade_question(&conn, "q1", "multi", &pick(&["m1", "s1"])).is_err());
+        assert!(grade_question(&conn, "q1", "tf", &write("true")).is_err());
+    }
+
+    fn shuffled(len: usize, seed: i64) -> Vec<usize> {
+        let mut items: Vec<usize> = (0..len).collect();
+        shuffle(&mut items, &mut (seed as u64));
+        items
+    }
+
+    #[test]
+    fn next_random_is_splitmix64() {
+        let mut state = 0;
+        assert_eq!(next_random(&mut state), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(next_random(&mut state), 0x6E78_9E6A_A1B9_65F4);
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_order() {
+        assert_eq!(shuffled(20, 42), shuffled(20, 42));
+        assert_eq!(shuffled(20, -7), shuffled(20, -7));
+        assert_ne!(shuffled(20, 42), shuffled(20, 43));
+    }
+
+    #[test]
+    fn shuffle_is_a_permutation() {
+        for seed in [0, 1, -1, i64::MIN, i64::MAX] {
+            let mut order = shuffled(50, seed);
+            assert_ne!(order, (0..50).collect::<Vec<_>>());
+            order.sort();
+            assert_eq!(order, (0..50).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn short_lists_are_left_alone() {
+        assert_eq!(shuffled(0, 42), Vec::<usize>::new());
+        assert_eq!(shuffled(1, 42), vec![0]);
+    }
+
+    #[test]
+    fn every_item_can_come_first() {
+        let mut firsts = [0; 4];
+        for seed in 0..400 {
+            firsts[shuffled(4, seed)[0]] += 1;
+        }
+        assert!(firsts.iter().all(|&n| n > 50), "{:?}", firsts);
+    }
+
+    // An open attempt on a quiz with one short-answer question
+    fn open_attempt() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE quizzes (id TEXT PRIMARY KEY, shuffle_questions BOOLEAN,
+                                   shuffle_options BOOLEAN);
+             CREATE TABLE quiz_attempts (id TEXT PRIMARY KEY, quiz_id TEXT, shuffle_seed INTEGER);
+             CREATE TABLE questions (id TEXT PRIMARY KEY, quiz_id TEXT, question_text TEXT,
+                                     image_url TEXT, points REAL, order_index INTEGER,
+                                     question_type TEXT, accepted_answers TEXT);
+             CREATE TABLE question_options (id TEXT PRIMARY KEY, question_id TEXT, option_text TEXT,
+                                            is_correct BOOLEAN, order_index INTEGER);
+             CREATE TABLE quiz_answers (attempt_id TEXT, question_id TEXT, selected_option_id TEXT,
+                                        is_correct BOOLEAN, points_earned REAL,
+                                        selected_option_ids TEXT, text_answer TEXT);
+             CREATE TABLE attempt_question_order (attempt_id TEXT, question_id TEXT,
+                                                  position INTEGER, option_order TEXT);
+             INSERT INTO quizzes VALUES ('q1', 0, 0);
+             INSERT INTO quiz_attempts VALUES ('a1', 'q1', 7);
+             INSERT INTO questions VALUES
+                 ('short', 'q1', 'Capital of France?', NULL, 1.0, 1, 'short_answer', '[\"Paris\"]');
+             INSERT INTO question_options VALUES ('x1', 'short', 'Paris, France', 1, 1);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn unrevealed_short_answers_carry_no_options() {
+        let conn = open_attempt();
+        let questions = load_attempt_questions(&conn, "a1", "q1", false).unwrap();
+        assert_eq!(questions[0]["options"], serde_json::json!([]));
+        assert!(questions[0].get("accepted_answers").is_none());
+        assert!(!JsonValue::Array(questions).to_string().contains("Paris"));
+
+        let revealed = load_attempt_questions(&conn, "a1", "q1", true).unwrap();
+        assert_eq!(revealed[0]["options"][0]["option_text"], "Paris, France");
+        assert_eq!(revealed[0]["accepted_answers"], serde_json::json!(["Paris"]));
+    }
+
+    #[test]
+    fn submitted_attempts_keep_their_recorded_grade() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE quizzes (id TEXT PRIMARY KEY, pass_mark_percentage REAL);
+             CREATE TABLE quiz_attempts (id TEXT PRIMARY KEY, quiz_id TEXT, status TEXT,
+                                         score REAL, passed BOOLEAN, deadline_at TEXT);
+             CREATE TABLE questions (id TEXT PRIMARY KEY, quiz_id TEXT, points REAL);
+             CREATE TABLE quiz_answers (id TEXT PRIMARY KEY, attempt_id TEXT, question_id TEXT,
+                                        is_correct BOOLEAN, points_earned REAL, answered_at TEXT);
+             INSERT INTO quizzes VALUES ('q1', 50);
+             INSERT INTO quiz_attempts VALUES ('a1', 'q1', 'completed', 100, 1, NULL);
+             INSERT INTO questions VALUES ('qq1', 'q1', 1.0);
+             INSERT INTO quiz_answers VALUES ('ans1', 'a1', 'qq1', 1, 1.0, NULL);",
+        )
+        .unwrap();
+
+        // Neither the attempt nor its answers are graded again, and no
+        // activity or review tables are touched
+        let grade = finalize_attempt(&conn, "a1").unwrap();
+        assert_eq!((grade.percentage, grade.passed), (100.0, true));
+        assert_eq!(grade.correct_answers, 1);
+
+        conn.execute("UPDATE quiz_attempts SET status = 'abandoned'", []).unwrap();
+        assert!(finalize_attempt(&conn, "a1").is_err());
+    }
+}
@@ -1,5 +1,5 @@
-use crate::commands::get_connection;
-use rusqlite::params;
+use crate::commands::{access, activity, get_connection};
+use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 
@@ -19,30 +19,57 @@ pub fn save_auth_tokens(
     access_expires_at: String,
     refresh_token: String,
     refresh_expires_at: String,
+    user_id: Option<String>,
 ) -> Result<String, String> {
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
-    // Clear existing tokens
-    conn.execute("DELETE FROM auth_tokens", [])
+    // Tokens belong to a profile. A login saves them before the user row
+    // exists, so the caller names the user; refreshes fall back to the
+    // signed-in learner.
+    let owner = match &user_id {
+        Some(id) => Some(id.clone()),
+        None => access::current_student_id(&conn),
+    };
+
+    // Clear this profile's existing tokens
+    conn.execute("DELETE FROM auth_tokens WHERE user_id IS ?1", params![owner])
         .map_err(|e| format!("Failed to clear old tokens: {}", e))?;
 
     // Insert access token
     conn.execute(
-        "INSERT INTO auth_tokens (token, token_type, expires_at, created_at, is_refresh_token)
-         VALUES (?1, ?2, ?3, datetime('now'), 0)",
-        params![access_token, "Bearer", access_expires_at],
+        "INSERT INTO auth_tokens (token, token_type, expires_at, created_at, is_refresh_token, user_id)
+         VALUES (?1, ?2, ?3, datetime('now'), 0, ?4)",
+        params![access_token, "Bearer", access_expires_at, owner],
     )
     .map_err(|e| format!("Failed to save access token: {}", e))?;
 
     // Insert refresh token
     conn.execute(
-        "INSERT INTO auth_tokens (token, token_type, expires_at, created_at, is_refresh_token)
-         VALUES (?1, ?2, ?3, datetime('now'), 1)",
-        params![refresh_token, "Bearer", refresh_expires_at],
+        "INSERT INTO auth_tokens (token, token_type, expires_at, created_at, is_refresh_token, user_id)
+         VALUES (?1, ?2, ?3, datetime('now'), 1, ?4)",
+        params![refresh_token, "Bearer", refresh_expires_at, owner],
     )
     .map_err(|e| format!("Failed to save refresh token: {}", e))?;
 
+    // ✅ Naming the user marks an online sign-in, the one way besides
+    // switch_profile to make a learner active
+    if let Some(user_id) = &user_id {
+        if let Some(previous) = access::current_student_id(&conn) {
+            if &previous != user_id {
+                activity::close_learner_sessions(&conn, &previous)?;
+            }
+        }
+
+        access::set_active_user(&conn, Some(user_id))?;
+
+        conn.execute(
+            "UPDATE users SET last_signed_in_at = datetime('now') WHERE id = ?1",
+            params![user_id],
+        )
+        .map_err(|e| format!("Failed to update profile: {}", e))?;
+    }
+
     Ok("Tokens saved successfully".to_string())
 }
 
@@ -51,12 +78,19 @@ pub fn get_auth_tokens(db_path: String) -> Result<(Option<AuthToken>, Option<Aut
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    // Tokens stay locked until a learner has signed in or unlocked a profile
+    let owner = match access::current_student_id(&conn) {
+        Some(owner) => owner,
+        None => return Ok((None, None)),
+    };
+
     // Get access token
     let access_token = conn
         .query_row(
             "SELECT token, token_type, expires_at, created_at FROM auth_tokens
-             WHERE is_refresh_token = 0 ORDER BY created_at DESC LIMIT 1",
-            [],
+             WHERE is_refresh_token = 0 AND user_id IS ?1
+             ORDER BY created_at DESC LIMIT 1",
+            params![owner],
             |row| {
                 Ok(AuthToken {
                     token: row.get(0)?,
@@ -73,8 +107,9 @@ pub fn get_auth_tokens(db_path: String) -> Result<(Option<AuthToken>, Option<Aut
     let refresh_token = conn
         .query_row(
             "SELECT token, token_type, expires_at, created_at FROM auth_tokens
-             WHERE is_refresh_token = 1 ORDER BY created_at DESC LIMIT 1",
-            [],
+             WHERE is_refresh_token = 1 AND user_id IS ?1
+             ORDER BY created_at DESC LIMIT 1",
+            params![owner],
             |row| {
                 Ok(AuthToken {
                     token: row.get(0)?,
@@ -95,14 +130,137 @@ pub fn clear_auth_tokens(db_path: String) -> Result<String, String> {
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
-    conn.execute("DELETE FROM auth_tokens", [])
+    let owner = access::current_student_id(&conn);
+
+    conn.execute("DELETE FROM auth_tokens WHERE user_id IS ?1", params![owner])
         .map_err(|e| format!("Failed to clear tokens: {}", e))?;
 
-    // Also clear user data on logout
-    conn.execute("DELETE FROM users", [])
-        .map_err(|e| format!("Failed to clear user data: {}", e))?;
+    if let Some(student_id) = &owner {
+        activity::close_learner_sessions(&conn, student_id)?;
+    }
+
+    // ✅ The profile and its progress stay cached so the learner can sign in
+    // again offline; remove_profile deletes them for good
+    access::set_active_user(&conn, None)?;
+
+    Ok("Tokens cleared successfully".to_string())
+}
+
+// ============================================================================
+// OFFLINE CREDENTIALS
+// ============================================================================
+// Taking over a cached profile requires its learner's password, checked
+// against a hash saved at their last online sign-in.
+
+pub const PROFILE_LOCKED: &str = "PROFILE_LOCKED";
+
+const CREDENTIAL_ROUNDS: u32 = 100_000;
+const MAX_CREDENTIAL_FAILURES: i64 = 5;
+
+fn hash_credential(password: &str, salt: &[u8], rounds: u32) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password.as_bytes(), salt, rounds, &mut hash);
+    hash
+}
+
+// Stored as "pbkdf2-sha256$<rounds>$<salt hex>$<hash hex>"
+fn encode_credential(password: &str) -> Result<String, String> {
+    let mut salt = [0u8; 16];
+    getrandom::getrandom(&mut salt).map_err(|e| format!("Failed to generate salt: {}", e))?;
+
+    let hash = hash_credential(password, &salt, CREDENTIAL_ROUNDS);
+    Ok(format!(
+        "pbkdf2-sha256${}${}${}",
+        CREDENTIAL_ROUNDS,
+        hex::encode(salt),
+        hex::encode(hash)
+    ))
+}
+
+fn credential_matches(stored: &str, password: &str) -> bool {
+    let parts: Vec<&str> = stored.split('$').collect();
+    let (rounds, salt, expected) = match parts.as_slice() {
+        ["pbkdf2-sha256", rounds, salt, hash] => match (rounds.parse(), hex::decode(salt), hex::decode(hash)) {
+            (Ok(rounds), Ok(salt), Ok(hash)) => (rounds, salt, hash),
+            _ => return false,
+        },
+        _ => return false,
+    };
+
+    let actual = hash_credential(password, &salt, rounds);
+
+    // Compare without an early exit
+    expected.len() == actual.len()
+        && expected.iter().zip(actual.iter()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn profile_locked_error() -> String {
+    format!(
+        "{}: This profile needs an online sign-in before it can be used on this device",
+        PROFILE_LOCKED
+    )
+}
+
+// Checks a learner's password before their profile is activated. Too many
+// wrong passwords discard the saved credential.
+pub fn verify_profile_credential(conn: &Connection, user_id: &str, password: &str) -> Result<(), String> {
+    let (stored, failures): (Option<String>, i64) = conn
+        .query_row(
+            "SELECT offline_credential, credential_failures FROM users WHERE id = ?1",
+            params![user_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Profile not found: {}", e))?;
+
+    let stored = stored.ok_or_else(profile_locked_error)?;
+
+    if credential_matches(&stored, password) {
+        conn.execute(
+            "UPDATE users SET credential_failures = 0 WHERE id = ?1",
+            params![user_id],
+        )
+        .map_err(|e| format!("Failed to update profile: {}", e))?;
+        return Ok(());
+    }
+
+    let failures = failures + 1;
+    if failures >= MAX_CREDENTIAL_FAILURES {
+        conn.execute(
+            "UPDATE users SET offline_credential = NULL, credential_failures = 0 WHERE id = ?1",
+            params![user_id],
+        )
+        .map_err(|e| format!("Failed to update profile: {}", e))?;
+        return Err(profile_locked_error());
+    }
+
+    conn.execute(
+        "UPDATE users SET credential_failures = ?1 WHERE id = ?2",
+        params![failures, user_id],
+    )
+    .map_err(|e| format!("Failed to update profile: {}", e))?;
+
+    Err("Incorrect password".to_string())
+}
+
+// Called after an online sign-in with the password the server just accepted
+#[tauri::command]
+pub fn save_offline_credential(db_path: String, password: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let user_id = access::require_student_id(&conn)?;
+
+    if password.is_empty() {
+        return Err("Password cannot be empty".to_string());
+    }
+
+    conn.execute(
+        "UPDATE users SET offline_credential = ?1, credential_failures = 0 WHERE id = ?2",
+        params![encode_credential(&password)?, user_id],
+    )
+    .map_err(|e| format!("Failed to save offline credential: {}", e))?;
 
-    Ok("Tokens and user data cleared successfully".to_string())
+    Ok("Offline credential saved successfully".to_string())
 }
 
 #[tauri::command]
@@ -136,14 +294,33 @@ pub fn save_user(db_path: String, user_data: String) -> Result<String, String> {
             Some(format!("{} {}", first, last).trim().to_string())
         })
         .ok_or("Missing full_name, first_name, or last_name")?;
+    let user_id = user["id"].as_str().ok_or("Missing user id")?;
 
+    // ✅ Upsert rather than REPLACE: a REPLACE deletes the row first, which
+    // cascades to the learner's enrollments and offline sessions
     conn.execute(
-        "INSERT OR REPLACE INTO users
+        "INSERT INTO users
          (id, email, first_name, middle_name, last_name, full_name, bio, phone_number,
-          role, is_active, profile_image_url, profile_image_file_id, created_at, updated_at, last_synced_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, datetime('now'))",
+          role, is_active, profile_image_url, profile_image_file_id, created_at, updated_at, last_synced_at,
+          last_signed_in_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, datetime('now'), datetime('now'))
+         ON CONFLICT(id) DO UPDATE SET
+            email = excluded.email,
+            first_name = excluded.first_name,
+            middle_name = excluded.middle_name,
+            last_name = excluded.last_name,
+            full_name = excluded.full_name,
+            bio = excluded.bio,
+            phone_number = excluded.phone_number,
+            role = excluded.role,
+            is_active = excluded.is_active,
+            profile_image_url = excluded.profile_image_url,
+            profile_image_file_id = excluded.profile_image_file_id,
+            created_at = excluded.created_at,
+            updated_at = excluded.updated_at,
+            last_synced_at = excluded.last_synced_at",
         params![
-            user["id"].as_str(),
+            user_id,
             user["email"].as_str(),
             user["first_name"].as_str(),
             user["middle_name"].as_str(),
@@ -186,7 +363,8 @@ pub fn get_current_user(db_path: String) -> Result<String, String> {
                 'profile_image_file_id', profile_image_file_id,
                 'created_at', created_at,
                 'updated_at', updated_at
-             ) FROM users LIMIT 1",
+             ) FROM users
+             WHERE id = (SELECT value FROM app_metadata WHERE key = 'active_user_id')",
             [],
             |row| row.get(0),
         )
@@ -0,0 +1,299 @@
+use crate::certificate::{self, CertificateDetails};
+use crate::commands::{access, get_connection};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value as JsonValue;
+use std::fs;
+
+// ============================================================================
+// PROVISIONAL CERTIFICATES
+// ============================================================================
+
+const CERTIFICATE_JSON: &str = "json_object(
+    'id', id,
+    'enrollment_id', enrollment_id,
+    'user_id', user_id,
+    'course_id', course_id,
+    'student_name', student_name,
+    'course_title', course_title,
+    'completed_at', completed_at,
+    'verification_code', verification_code,
+    'file_path', file_path,
+    'status', status,
+    'is_provisional', status = 'provisional',
+    'official_certificate_id', official_certificate_id,
+    'official_certificate_number', official_certificate_number,
+    'official_verification_token', official_verification_token,
+    'replaced_at', replaced_at,
+    'created_at', created_at,
+    'updated_at', updated_at
+)";
+
+// Records a provisional certificate for a completed enrollment. Returns the
+// existing certificate id when one was already issued.
+pub fn issue_provisional_certificate(
+    conn: &Connection,
+    enrollment_id: &str,
+) -> Result<String, String> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM provisional_certificates WHERE enrollment_id = ?1",
+            params![enrollment_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to load certificate: {}", e))?;
+
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let (user_id, course_id, status, completed_at, student_name, course_title): (
+        String,
+        String,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+    ) = conn
+        .query_row(
+            "SELECT e.student_id, e.course_id, e.status, e.completed_at, u.full_name, c.title
+             FROM enrollments e
+             LEFT JOIN users u ON e.student_id = u.id
+             LEFT JOIN courses c ON e.course_id = c.id
+             WHERE e.id = ?1",
+            params![enrollment_id],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                ))
+            },
+        )
+        .map_err(|e| format!("Enrollment not found: {}", e))?;
+
+    if status.as_deref() != Some("completed") {
+        return Err(format!("Enrollment {} has not completed the course", enrollment_id));
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let certificate_id = format!("provisional_{}", enrollment_id);
+    let verification_code = certificate::generate_verification_code()?;
+
+    conn.execute(
+        "INSERT INTO provisional_certificates
+         (id, enrollment_id, user_id, course_id, student_name, course_title, completed_at,
+          verification_code, status, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 'provisional', ?9, ?9)",
+        params![
+            certificate_id,
+            enrollment_id,
+            user_id,
+            course_id,
+            student_name.unwrap_or_default(),
+            course_title.unwrap_or_default(),
+            completed_at.unwrap_or_else(|| now.clone()),
+            verification_code,
+            now,
+        ],
+    )
+    .map_err(|e| format!("Failed to save certificate: {}", e))?;
+
+    println!("📜 Provisional certificate {} issued ({})", certificate_id, verification_code);
+
+    Ok(certificate_id)
+}
+
+fn load_certificate_json(conn: &Connection, certificate_id: &str) -> Result<String, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM provisional_certificates WHERE id = ?1", CERTIFICATE_JSON),
+        params![certificate_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Certificate not found: {}", e))
+}
+
+// Issues the certificate if needed and writes its PDF. Replaced certificates
+// are returned as-is; the official one is fetched from the server.
+#[tauri::command]
+pub fn generate_provisional_certificate(
+    db_path: String,
+    enrollment_id: String,
+) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    // Only the learner who completed the course gets their certificate issued
+    access::ensure_own_enrollment(&conn, &enrollment_id)?;
+
+    let certificate_id = issue_provisional_certificate(&conn, &enrollment_id)?;
+
+    let (status, file_path, student_name, course_title, completed_at, verification_code): (
+        String,
+        Option<String>,
+        String,
+        String,
+        String,
+        String,
+    ) = conn
+        .query_row(
+            "SELECT status, file_path, student_name, course_title, completed_at, verification_code
+             FROM provisional_certificates WHERE id = ?1",
+            params![certificate_id],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                ))
+            },
+        )
+        .map_err(|e| format!("Certificate not found: {}", e))?;
+
+    let already_rendered = file_path.as_deref().map_or(false, |p| fs::metadata(p).is_ok());
+
+    if status == "provisional" && !already_rendered {
+        let pdf = certificate::render_certificate_pdf(&CertificateDetails {
+            student_name: &student_name,
+            course_title: &course_title,
+            completed_at: &completed_at,
+            verification_code: &verification_code,
+        });
+
+        let dir = certificate::get_certificate_dir(&db_path)?;
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create certificate directory: {}", e))?;
+
+        let path = dir.join(format!("{}.pdf", certificate_id));
+        fs::write(&path, pdf).map_err(|e| format!("Failed to write certificate: {}", e))?;
+
+        conn.execute(
+            "UPDATE provisional_certificates SET file_path = ?1, updated_at = ?2 WHERE id = ?3",
+            params![
+                path.to_string_lossy().to_string(),
+                chrono::Utc::now().to_rfc3339(),
+                certificate_id
+            ],
+        )
+        .map_err(|e| format!("Failed to update certificate: {}", e))?;
+    }
+
+    load_certificate_json(&conn, &certificate_id)
+}
+
+// The current learner's certificate for a course, or null
+#[tauri::command]
+pub fn get_provisional_certificate(db_path: String, course_id: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let student_id = match access::current_student_id(&conn) {
+        Some(id) => id,
+        None => return Ok("null".to_string()),
+    };
+
+    let certificate_json: Option<String> = conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM provisional_certificates
+                 WHERE user_id = ?1 AND course_id = ?2",
+                CERTIFICATE_JSON
+            ),
+            params![student_id, course_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to load certificate: {}", e))?;
+
+    Ok(certificate_json.unwrap_or_else(|| "null".to_string()))
+}
+
+// Certificates whose verification code still has to be sent to the server
+#[tauri::command]
+pub fn get_pending_provisional_certificates(db_path: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM provisional_certificates
+             WHERE status = 'provisional'
+             ORDER BY completed_at ASC",
+            CERTIFICATE_JSON
+        ))
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let certificates: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(format!("[{}]", certificates.join(",")))
+}
+
+// Called during sync once the server has confirmed the verification code and
+// issued the official certificate
+#[tauri::command]
+pub fn replace_provisional_certificate(
+    db_path: String,
+    verification_code: String,
+    certificate_data: String,
+) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let official: JsonValue = serde_json::from_str(&certificate_data)
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let official_id = official["id"]
+        .as_str()
+        .ok_or("Missing official certificate id")?;
+
+    let (certificate_id, file_path): (String, Option<String>) = conn
+        .query_row(
+            "SELECT id, file_path FROM provisional_certificates WHERE verification_code = ?1",
+            params![verification_code],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|_| format!("No provisional certificate with code {}", verification_code))?;
+
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        "UPDATE provisional_certificates
+         SET status = 'replaced',
+             official_certificate_id = ?1,
+             official_certificate_number = ?2,
+             official_verification_token = ?3,
+             file_path = NULL,
+             replaced_at = ?4,
+             updated_at = ?4
+         WHERE id = ?5",
+        params![
+            official_id,
+            official["certificate_number"].as_str(),
+            official["verification_token"].as_str(),
+            now,
+            certificate_id
+        ],
+    )
+    .map_err(|e| format!("Failed to replace certificate: {}", e))?;
+
+    // The provisional PDF must not outlive the official certificate
+    if let Some(path) = file_path {
+        if let Err(e) = fs::remove_file(&path) {
+            println!("⚠️ Could not delete provisional certificate {}: {}", path, e);
+        }
+    }
+
+    println!("📜 Provisional certificate {} replaced by {}", certificate_id, official_id);
+
+    load_certificate_json(&conn, &certificate_id)
+}
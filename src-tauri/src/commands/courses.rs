@@ -1,11 +1,34 @@
-use crate::commands::get_connection;
-use rusqlite::params;
+use crate::commands::{access, get_connection, search};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
 use serde_json::Value as JsonValue;
 
 // ============================================================================
 // COURSE COMMANDS
 // ============================================================================
 
+// ✅ Upsert rather than REPLACE: a REPLACE deletes the row first, which
+// cascades to the course's enrollments and everything hanging off them
+const UPSERT_COURSE: &str = "INSERT INTO courses
+     (id, title, description, image_id, created_by, is_published,
+      module_count, enrollment_count, category, level, duration,
+      created_at, updated_at, last_synced_at)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, datetime('now'))
+     ON CONFLICT(id) DO UPDATE SET
+        title = excluded.title,
+        description = excluded.description,
+        image_id = excluded.image_id,
+        created_by = excluded.created_by,
+        is_published = excluded.is_published,
+        module_count = excluded.module_count,
+        enrollment_count = excluded.enrollment_count,
+        category = excluded.category,
+        level = excluded.level,
+        duration = excluded.duration,
+        created_at = excluded.created_at,
+        updated_at = excluded.updated_at,
+        last_synced_at = excluded.last_synced_at";
+
 #[tauri::command]
 pub fn save_course(db_path: String, course_data: String) -> Result<String, String> {
     let conn = get_connection(&db_path)
@@ -22,11 +45,7 @@ pub fn save_course(db_path: String, course_data: String) -> Result<String, Strin
     };
 
     conn.execute(
-        "INSERT OR REPLACE INTO courses
-         (id, title, description, image_id, created_by, is_published,
-          module_count, enrollment_count, category, level, duration,
-          created_at, updated_at, last_synced_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, datetime('now'))",
+        UPSERT_COURSE,
         params![
             course["id"].as_str(),
             course["title"].as_str(),
@@ -45,6 +64,13 @@ pub fn save_course(db_path: String, course_data: String) -> Result<String, Strin
     )
     .map_err(|e| format!("Failed to save course: {}", e))?;
 
+    if let Some(course_id) = course["id"].as_str() {
+        if let Some(policy) = course["access_policy"].as_str() {
+            access::save_course_policy(&conn, course_id, policy)?;
+        }
+        search::index_course(&conn, course_id)?;
+    }
+
     Ok("Course saved successfully".to_string())
 }
 
@@ -66,11 +92,7 @@ pub fn save_courses_bulk(db_path: String, courses_data: String) -> Result<String
         };
 
         conn.execute(
-            "INSERT OR REPLACE INTO courses
-             (id, title, description, image_id, created_by, is_published,
-              module_count, enrollment_count, category, level, duration,
-              created_at, updated_at, last_synced_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, datetime('now'))",
+            UPSERT_COURSE,
             params![
                 course["id"].as_str(),
                 course["title"].as_str(),
@@ -88,6 +110,13 @@ pub fn save_courses_bulk(db_path: String, courses_data: String) -> Result<String
             ],
         )
         .map_err(|e| format!("Failed to save course: {}", e))?;
+
+        if let Some(course_id) = course["id"].as_str() {
+            if let Some(policy) = course["access_policy"].as_str() {
+                access::save_course_policy(&conn, course_id, policy)?;
+            }
+            search::index_course(&conn, course_id)?;
+        }
         count += 1;
     }
 
@@ -142,6 +171,329 @@ pub fn get_all_courses(db_path: String) -> Result<String, String> {
     Ok(courses_json)
 }
 
+// ============================================================================
+// COURSE CATALOGUE
+// ============================================================================
+// query_courses returns one page of the catalogue at a time. Pages are keyed
+// on the last row's sort value and id rather than an offset, so deep pages
+// stay as cheap as the first one.
+
+const CATALOG_PAGE_SIZE: i64 = 20;
+const CATALOG_MAX_PAGE_SIZE: i64 = 100;
+
+// (sort name, key expression, direction); ties are broken by id in the same
+// direction
+const CATALOG_SORTS: [(&str, &str, &str); 3] = [
+    ("newest", "c.created_at", "DESC"),
+    ("title", "c.title COLLATE NOCASE", "ASC"),
+    ("popular", "COALESCE(c.enrollment_count, 0)", "DESC"),
+];
+
+// The learner's enrollments are joined once; its single parameter is the
+// student id and always comes first
+const CATALOG_FROM: &str = "courses c
+ LEFT JOIN course_media cm ON c.image_id = cm.id
+ LEFT JOIN (
+    SELECT course_id, MAX(status) AS status, MIN(enrolled_at) AS enrolled_at
+    FROM enrollments WHERE student_id = ? GROUP BY course_id
+ ) e ON e.course_id = c.id";
+
+const IS_DOWNLOADED: &str =
+    "EXISTS (SELECT 1 FROM offline_sessions os WHERE os.course_id = c.id AND os.is_deleted = 0)";
+
+struct CatalogFilter {
+    facet: Option<&'static str>,
+    sql: String,
+    values: Vec<Value>,
+}
+
+// Accepts a single value or an array of values
+fn string_list(value: &JsonValue) -> Vec<String> {
+    match value {
+        JsonValue::String(s) => vec![s.clone()],
+        JsonValue::Array(items) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(|s| s.to_string()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn catalog_filters(query: &JsonValue) -> Vec<CatalogFilter> {
+    let mut filters = Vec::new();
+
+    for (facet, column, uppercase) in [("category", "c.category", false), ("level", "c.level", true)] {
+        let values: Vec<Value> = string_list(&query[facet])
+            .into_iter()
+            .map(|v| Value::Text(if uppercase { v.to_uppercase() } else { v }))
+            .collect();
+
+        if !values.is_empty() {
+            filters.push(CatalogFilter {
+                facet: Some(facet),
+                sql: format!("{} IN ({})", column, vec!["?"; values.len()].join(", ")),
+                values,
+            });
+        }
+    }
+
+    if let Some(published) = query["is_published"].as_bool() {
+        filters.push(CatalogFilter {
+            facet: None,
+            sql: "COALESCE(c.is_published, 0) = ?".to_string(),
+            values: vec![Value::Integer(published as i64)],
+        });
+    }
+
+    for (field, operator) in [("min_duration", ">="), ("max_duration", "<=")] {
+        if let Some(duration) = query[field].as_i64() {
+            filters.push(CatalogFilter {
+                facet: None,
+                sql: format!("COALESCE(c.duration, 0) {} ?", operator),
+                values: vec![Value::Integer(duration)],
+            });
+        }
+    }
+
+    if let Some(enrolled) = query["enrolled"].as_bool() {
+        filters.push(CatalogFilter {
+            facet: None,
+            sql: format!("e.course_id IS {}NULL", if enrolled { "NOT " } else { "" }),
+            values: Vec::new(),
+        });
+    }
+
+    if let Some(downloaded) = query["downloaded"].as_bool() {
+        filters.push(CatalogFilter {
+            facet: None,
+            sql: format!("{}{}", if downloaded { "" } else { "NOT " }, IS_DOWNLOADED),
+            values: Vec::new(),
+        });
+    }
+
+    filters
+}
+
+// WHERE clause and its values, leaving out the filter on `skip_facet` so a
+// facet's counts show what selecting another value would return
+fn catalog_where(filters: &[CatalogFilter], skip_facet: Option<&str>) -> (String, Vec<Value>) {
+    let applied: Vec<&CatalogFilter> = filters
+        .iter()
+        .filter(|f| skip_facet.is_none() || f.facet != skip_facet)
+        .collect();
+
+    let sql = if applied.is_empty() {
+        "1 = 1".to_string()
+    } else {
+        applied.iter().map(|f| f.sql.as_str()).collect::<Vec<_>>().join(" AND ")
+    };
+    let values = applied.iter().flat_map(|f| f.values.iter().cloned()).collect();
+
+    (sql, values)
+}
+
+// Cursors are opaque to the caller: hex-encoded [sort, key, id]
+fn encode_cursor(sort: &str, key: &Value, id: &str) -> String {
+    let key = match key {
+        Value::Integer(n) => JsonValue::from(*n),
+        Value::Real(n) => JsonValue::from(*n),
+        Value::Text(s) => JsonValue::from(s.as_str()),
+        _ => JsonValue::Null,
+    };
+
+    hex::encode(serde_json::json!([sort, key, id]).to_string())
+}
+
+fn decode_cursor(cursor: &str, sort: &str) -> Result<(Value, String), String> {
+    let invalid = || "Invalid cursor".to_string();
+
+    let bytes = hex::decode(cursor).map_err(|_| invalid())?;
+    let parts: JsonValue = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+
+    if parts[0].as_str() != Some(sort) {
+        return Err("Cursor belongs to a different sort order".to_string());
+    }
+
+    let key = match &parts[1] {
+        JsonValue::Number(n) if n.is_i64() => Value::Integer(n.as_i64().unwrap_or_default()),
+        JsonValue::Number(n) => Value::Real(n.as_f64().unwrap_or_default()),
+        JsonValue::String(s) => Value::Text(s.clone()),
+        _ => Value::Null,
+    };
+    let id = parts[2].as_str().ok_or_else(invalid)?.to_string();
+
+    Ok((key, id))
+}
+
+fn facet_counts(
+    conn: &Connection,
+    student_id: &str,
+    filters: &[CatalogFilter],
+    facet: &str,
+    column: &str,
+) -> Result<Vec<JsonValue>, String> {
+    let (where_sql, values) = catalog_where(filters, Some(facet));
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {col}, COUNT(*) FROM {from}
+             WHERE {where_sql} AND {col} IS NOT NULL
+             GROUP BY {col}
+             ORDER BY COUNT(*) DESC, {col} ASC",
+            col = column,
+            from = CATALOG_FROM,
+            where_sql = where_sql
+        ))
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let params = std::iter::once(Value::Text(student_id.to_string())).chain(values);
+    let counts = stmt
+        .query_map(params_from_iter(params), |row| {
+            Ok(serde_json::json!({
+                "value": row.get::<_, String>(0)?,
+                "count": row.get::<_, i64>(1)?
+            }))
+        })
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(counts)
+}
+
+// query_data: { category, level (single value or array), is_published,
+// min_duration, max_duration, enrolled, downloaded, sort (newest | title |
+// popular), cursor, limit }
+#[tauri::command]
+pub fn query_courses(db_path: String, query_data: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let query: JsonValue = serde_json::from_str(&query_data)
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let sort = query["sort"].as_str().unwrap_or("newest");
+    let (_, sort_key, direction) = CATALOG_SORTS
+        .iter()
+        .find(|(name, _, _)| *name == sort)
+        .ok_or_else(|| format!("Unknown sort '{}'. Expected newest, title or popular", sort))?;
+
+    let limit = query["limit"]
+        .as_i64()
+        .unwrap_or(CATALOG_PAGE_SIZE)
+        .clamp(1, CATALOG_MAX_PAGE_SIZE);
+
+    // Enrollment filters match nothing until someone signs in
+    let student_id = access::current_student_id(&conn).unwrap_or_default();
+    let filters = catalog_filters(&query);
+    let (where_sql, filter_values) = catalog_where(&filters, None);
+
+    let total: i64 = conn
+        .query_row(
+            &format!("SELECT COUNT(*) FROM {} WHERE {}", CATALOG_FROM, where_sql),
+            params_from_iter(
+                std::iter::once(Value::Text(student_id.clone())).chain(filter_values.iter().cloned()),
+            ),
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to count courses: {}", e))?;
+
+    let mut page_sql = where_sql.clone();
+    let mut page_values = filter_values;
+    if let Some(cursor) = query["cursor"].as_str() {
+        let (key, id) = decode_cursor(cursor, sort)?;
+        page_sql.push_str(&format!(
+            " AND ({}, c.id) {} (?, ?)",
+            sort_key,
+            if *direction == "ASC" { ">" } else { "<" }
+        ));
+        page_values.push(key);
+        page_values.push(Value::Text(id));
+    }
+    // One extra row tells whether another page follows
+    page_values.push(Value::Integer(limit + 1));
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT json_object(
+                'id', c.id,
+                'title', c.title,
+                'description', c.description,
+                'image', CASE
+                    WHEN cm.id IS NOT NULL THEN json_object(
+                        'id', cm.id,
+                        'file_id', cm.file_id,
+                        'filename', cm.filename,
+                        'media_type', cm.media_type,
+                        'public_url', cm.public_url,
+                        'size_bytes', cm.size_bytes,
+                        'uploaded_by', cm.uploaded_by,
+                        'created_at', cm.created_at
+                    )
+                    ELSE NULL
+                END,
+                'is_published', c.is_published,
+                'module_count', c.module_count,
+                'enrollment_count', c.enrollment_count,
+                'category', c.category,
+                'level', c.level,
+                'duration', c.duration,
+                'is_enrolled', e.course_id IS NOT NULL,
+                'enrollment_status', e.status,
+                'enrolled_at', e.enrolled_at,
+                'is_downloaded', {downloaded},
+                'created_at', c.created_at,
+                'updated_at', c.updated_at
+             ), {key}, c.id
+             FROM {from}
+             WHERE {where_sql}
+             ORDER BY {key} {dir}, c.id {dir}
+             LIMIT ?",
+            downloaded = IS_DOWNLOADED,
+            key = sort_key,
+            from = CATALOG_FROM,
+            where_sql = page_sql,
+            dir = direction
+        ))
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let mut rows: Vec<(String, Value, String)> = stmt
+        .query_map(
+            params_from_iter(std::iter::once(Value::Text(student_id.clone())).chain(page_values)),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let has_more = rows.len() as i64 > limit;
+    rows.truncate(limit as usize);
+
+    let next_cursor = match (has_more, rows.last()) {
+        (true, Some((_, key, id))) => Some(encode_cursor(sort, key, id)),
+        _ => None,
+    };
+
+    let courses: Vec<JsonValue> = rows
+        .iter()
+        .filter_map(|(json, _, _)| serde_json::from_str(json).ok())
+        .collect();
+
+    let page = serde_json::json!({
+        "courses": courses,
+        "total": total,
+        "sort": sort,
+        "has_more": has_more,
+        "next_cursor": next_cursor,
+        "facets": {
+            "category": facet_counts(&conn, &student_id, &filters, "category", "c.category")?,
+            "level": facet_counts(&conn, &student_id, &filters, "level", "c.level")?
+        }
+    });
+
+    Ok(page.to_string())
+}
+
 #[tauri::command]
 pub fn get_enrolled_courses(db_path: String, student_id: String) -> Result<String, String> {
     let conn = get_connection(&db_path)
@@ -249,10 +601,20 @@ pub fn save_course_media(db_path: String, media_data: String) -> Result<String,
     let media: JsonValue = serde_json::from_str(&media_data)
         .map_err(|e| format!("Invalid JSON: {}", e))?;
 
+    // ✅ Upsert rather than REPLACE, which would cascade to the media cache
     conn.execute(
-        "INSERT OR REPLACE INTO course_media
+        "INSERT INTO course_media
          (id, file_id, filename, media_type, public_url, size_bytes, uploaded_by, created_at, last_synced_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now'))",
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now'))
+         ON CONFLICT(id) DO UPDATE SET
+            file_id = excluded.file_id,
+            filename = excluded.filename,
+            media_type = excluded.media_type,
+            public_url = excluded.public_url,
+            size_bytes = excluded.size_bytes,
+            uploaded_by = excluded.uploaded_by,
+            created_at = excluded.created_at,
+            last_synced_at = excluded.last_synced_at",
         params![
             media["id"].as_str(),
             media["file_id"].as_str(),
@@ -282,9 +644,25 @@ pub fn save_enrollment(db_path: String, enrollment_data: String) -> Result<Strin
         .map_err(|e| format!("Invalid JSON: {}", e))?;
 
     conn.execute(
-        "INSERT OR REPLACE INTO enrollments
+        "INSERT INTO enrollments
          (id, student_id, course_id, status, enrolled_at, completed_at, created_at, updated_at, last_synced_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now'))",
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now'))
+         ON CONFLICT(id) DO UPDATE SET
+            student_id = excluded.student_id,
+            course_id = excluded.course_id,
+            -- A completion recorded offline survives a refresh until it has synced
+            status = CASE
+                WHEN enrollments.status = 'completed' AND excluded.status = 'active' THEN enrollments.status
+                ELSE excluded.status
+            END,
+            completed_at = CASE
+                WHEN enrollments.status = 'completed' AND excluded.status = 'active' THEN enrollments.completed_at
+                ELSE excluded.completed_at
+            END,
+            enrolled_at = excluded.enrolled_at,
+            created_at = excluded.created_at,
+            updated_at = excluded.updated_at,
+            last_synced_at = excluded.last_synced_at",
         params![
             enrollment["id"].as_str(),
             enrollment["student_id"].as_str(),
@@ -380,3 +758,194 @@ pub fn check_enrollment_exists(
 
     Ok(count > 0)
 }
+
+// ============================================================================
+// PREREQUISITE COMMANDS
+// ============================================================================
+
+// Replaces the course's prerequisites with the list from the course package
+#[tauri::command]
+pub fn save_course_prerequisites(
+    db_path: String,
+    course_id: String,
+    prerequisites_data: String,
+) -> Result<String, String> {
+    let mut conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let prerequisites: Vec<JsonValue> = serde_json::from_str(&prerequisites_data)
+        .map_err(|e| format!("Invalid JSON array: {}", e))?;
+
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    tx.execute(
+        "DELETE FROM course_prerequisites WHERE course_id = ?1",
+        params![course_id],
+    )
+    .map_err(|e| format!("Failed to clear prerequisites: {}", e))?;
+
+    for prerequisite in &prerequisites {
+        let prerequisite_course_id = prerequisite["prerequisite_course_id"]
+            .as_str()
+            .ok_or("Missing prerequisite_course_id")?;
+
+        let id = prerequisite["id"]
+            .as_str()
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| format!("{}_{}", course_id, prerequisite_course_id));
+
+        tx.execute(
+            "INSERT OR REPLACE INTO course_prerequisites
+             (id, course_id, prerequisite_course_id, prerequisite_title, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                id,
+                course_id,
+                prerequisite_course_id,
+                prerequisite["prerequisite_course"]["title"].as_str(),
+                prerequisite["created_at"].as_str().unwrap_or(&now),
+            ],
+        )
+        .map_err(|e| format!("Failed to save prerequisite: {}", e))?;
+    }
+
+    tx.commit()
+        .map_err(|e| format!("Failed to save prerequisites: {}", e))?;
+
+    Ok(format!("{} prerequisites saved successfully", prerequisites.len()))
+}
+
+#[tauri::command]
+pub fn get_course_prerequisites(db_path: String, course_id: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT json_object(
+                'id', cp.id,
+                'course_id', cp.course_id,
+                'prerequisite_course_id', cp.prerequisite_course_id,
+                'prerequisite_course', json_object(
+                    'id', cp.prerequisite_course_id,
+                    'title', COALESCE(c.title, cp.prerequisite_title),
+                    'description', c.description,
+                    'category', c.category,
+                    'level', c.level,
+                    'duration', c.duration
+                ),
+                'created_at', cp.created_at
+             ) FROM course_prerequisites cp
+             LEFT JOIN courses c ON cp.prerequisite_course_id = c.id
+             WHERE cp.course_id = ?1
+             ORDER BY cp.created_at ASC",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let prerequisites: Vec<String> = stmt
+        .query_map(params![course_id], |row| row.get(0))
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let prerequisites_json = format!("[{}]", prerequisites.join(","));
+    Ok(prerequisites_json)
+}
+
+// Same shape as the server's enrollment eligibility check
+#[tauri::command]
+pub fn check_course_eligibility(
+    db_path: String,
+    course_id: String,
+    student_id: String,
+) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let prerequisites = access::get_prerequisite_status(&conn, &course_id, &student_id)?;
+
+    let missing: Vec<JsonValue> = prerequisites
+        .iter()
+        .filter(|p| !p.is_completed)
+        .map(|p| {
+            serde_json::json!({
+                "id": p.course_id,
+                "title": p.title,
+                "description": p.description,
+                "image_url": p.image_url,
+                "is_completed": p.is_completed
+            })
+        })
+        .collect();
+
+    let message = if missing.is_empty() {
+        "All prerequisites are complete".to_string()
+    } else {
+        format!(
+            "{} of {} prerequisites still need to be completed",
+            missing.len(),
+            prerequisites.len()
+        )
+    };
+
+    let eligibility = serde_json::json!({
+        "eligible": missing.is_empty(),
+        "message": message,
+        "total_prerequisites": prerequisites.len(),
+        "missing_prerequisites": missing
+    });
+
+    Ok(eligibility.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursors_round_trip() {
+        for key in [
+            Value::Integer(42),
+            Value::Integer(-1),
+            Value::Real(87.5),
+            Value::Real(4.0),
+            Value::Text("Biology \"101\" – ünïcode".to_string()),
+            Value::Null,
+        ] {
+            let cursor = encode_cursor("title", &key, "c1");
+            assert_eq!(decode_cursor(&cursor, "title"), Ok((key, "c1".to_string())));
+        }
+    }
+
+    #[test]
+    fn cursors_are_opaque_hex() {
+        let cursor = encode_cursor("newest", &Value::Text("2024-01-01".to_string()), "c1");
+        assert!(cursor.chars().all(|c| c.is_ascii_hexdigit()));
+        assert!(!cursor.contains("newest"));
+    }
+
+    #[test]
+    fn a_cursor_only_fits_its_sort_order() {
+        let cursor = encode_cursor("title", &Value::Text("A".to_string()), "c1");
+        assert_eq!(
+            decode_cursor(&cursor, "newest"),
+            Err("Cursor belongs to a different sort order".to_string())
+        );
+    }
+
+    #[test]
+    fn tampered_cursors_are_rejected() {
+        let invalid = Err("Invalid cursor".to_string());
+        assert_eq!(decode_cursor("not hex", "title"), invalid);
+        assert_eq!(decode_cursor("abc", "title"), invalid);
+        assert_eq!(decode_cursor(&hex::encode("[\"title\", 1"), "title"), invalid);
+        assert_eq!(decode_cursor(&hex::encode("[\"title\", 1, 7]"), "title"), invalid);
+        assert_eq!(decode_cursor(&hex::encode("[\"title\", 1]"), "title"), invalid);
+        assert!(decode_cursor(&hex::encode("{}"), "title").is_err());
+        assert!(decode_cursor("", "title").is_err());
+    }
+}
@@ -1,11 +1,41 @@
-use crate::commands::get_connection;
-use rusqlite::params;
+use crate::commands::{access, assessment, get_connection, progress, search};
+use rusqlite::{params, Connection};
 use serde_json::Value as JsonValue;
 
 // ============================================================================
 // MODULE COMMANDS
 // ============================================================================
 
+// ✅ Upserts rather than REPLACE: with foreign keys on, a REPLACE deletes the
+// row first, which cascades to everything hanging off it (a module's content,
+// quizzes and progress; a quiz's attempts; a question's answers)
+const UPSERT_MODULE: &str = "INSERT INTO modules
+     (id, course_id, title, description, order_index, content_count, has_quiz,
+      created_at, updated_at, last_synced_at)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, datetime('now'))
+     ON CONFLICT(id) DO UPDATE SET
+        course_id = excluded.course_id,
+        title = excluded.title,
+        description = excluded.description,
+        order_index = excluded.order_index,
+        content_count = excluded.content_count,
+        has_quiz = excluded.has_quiz,
+        created_at = excluded.created_at,
+        updated_at = excluded.updated_at,
+        last_synced_at = excluded.last_synced_at";
+
+const UPSERT_CONTENT_BLOCK: &str = "INSERT INTO content_blocks
+     (id, module_id, title, content_data, order_index, created_at, updated_at, last_synced_at)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, datetime('now'))
+     ON CONFLICT(id) DO UPDATE SET
+        module_id = excluded.module_id,
+        title = excluded.title,
+        content_data = excluded.content_data,
+        order_index = excluded.order_index,
+        created_at = excluded.created_at,
+        updated_at = excluded.updated_at,
+        last_synced_at = excluded.last_synced_at";
+
 #[tauri::command]
 pub fn save_module(db_path: String, module_data: String) -> Result<String, String> {
     let conn = get_connection(&db_path)
@@ -15,9 +45,7 @@ pub fn save_module(db_path: String, module_data: String) -> Result<String, Strin
         .map_err(|e| format!("Invalid JSON: {}", e))?;
 
     conn.execute(
-        "INSERT OR REPLACE INTO modules
-         (id, course_id, title, description, order_index, content_count, has_quiz, created_at, updated_at, last_synced_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, datetime('now'))",
+        UPSERT_MODULE,
         params![
             module["id"].as_str(),
             module["course_id"].as_str(),
@@ -32,6 +60,11 @@ pub fn save_module(db_path: String, module_data: String) -> Result<String, Strin
     )
     .map_err(|e| format!("Failed to save module: {}", e))?;
 
+    if let Some(module_id) = module["id"].as_str() {
+        search::index_module(&conn, module_id)?;
+        progress::recompute_after_import(&conn, &[module_id.to_string()])?;
+    }
+
     Ok("Module saved successfully".to_string())
 }
 
@@ -44,11 +77,10 @@ pub fn save_modules_bulk(db_path: String, modules_data: String) -> Result<String
         .map_err(|e| format!("Invalid JSON array: {}", e))?;
 
     let mut count = 0;
+    let mut module_ids = Vec::new();
     for module in modules {
         conn.execute(
-            "INSERT OR REPLACE INTO modules
-             (id, course_id, title, description, order_index, content_count, has_quiz, created_at, updated_at, last_synced_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, datetime('now'))",
+            UPSERT_MODULE,
             params![
                 module["id"].as_str(),
                 module["course_id"].as_str(),
@@ -62,9 +94,16 @@ pub fn save_modules_bulk(db_path: String, modules_data: String) -> Result<String
             ],
         )
         .map_err(|e| format!("Failed to save module: {}", e))?;
+
+        if let Some(module_id) = module["id"].as_str() {
+            search::index_module(&conn, module_id)?;
+            module_ids.push(module_id.to_string());
+        }
         count += 1;
     }
 
+    progress::recompute_after_import(&conn, &module_ids)?;
+
     Ok(format!("{} modules saved successfully", count))
 }
 
@@ -122,6 +161,8 @@ pub fn get_module_by_id(db_path: String, module_id: String) -> Result<String, St
         )
         .map_err(|e| format!("Module not found: {}", e))?;
 
+    access::ensure_module_access(&conn, &module_id)?;
+
     Ok(module_json)
 }
 
@@ -138,9 +179,7 @@ pub fn save_content_block(db_path: String, content_data: String) -> Result<Strin
         .map_err(|e| format!("Invalid JSON: {}", e))?;
 
     conn.execute(
-        "INSERT OR REPLACE INTO content_blocks
-         (id, module_id, title, content_data, order_index, created_at, updated_at, last_synced_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, datetime('now'))",
+        UPSERT_CONTENT_BLOCK,
         params![
             content["id"].as_str(),
             content["module_id"].as_str(),
@@ -153,6 +192,13 @@ pub fn save_content_block(db_path: String, content_data: String) -> Result<Strin
     )
     .map_err(|e| format!("Failed to save content block: {}", e))?;
 
+    if let Some(content_id) = content["id"].as_str() {
+        search::index_content_block(&conn, content_id)?;
+    }
+    if let Some(module_id) = content["module_id"].as_str() {
+        progress::recompute_after_import(&conn, &[module_id.to_string()])?;
+    }
+
     Ok("Content block saved successfully".to_string())
 }
 
@@ -165,11 +211,10 @@ pub fn save_content_blocks_bulk(db_path: String, contents_data: String) -> Resul
         .map_err(|e| format!("Invalid JSON array: {}", e))?;
 
     let mut count = 0;
+    let mut module_ids: Vec<String> = Vec::new();
     for content in contents {
         conn.execute(
-            "INSERT OR REPLACE INTO content_blocks
-             (id, module_id, title, content_data, order_index, created_at, updated_at, last_synced_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, datetime('now'))",
+            UPSERT_CONTENT_BLOCK,
             params![
                 content["id"].as_str(),
                 content["module_id"].as_str(),
@@ -181,9 +226,20 @@ pub fn save_content_blocks_bulk(db_path: String, contents_data: String) -> Resul
             ],
         )
         .map_err(|e| format!("Failed to save content block: {}", e))?;
+
+        if let Some(content_id) = content["id"].as_str() {
+            search::index_content_block(&conn, content_id)?;
+        }
+        if let Some(module_id) = content["module_id"].as_str() {
+            if !module_ids.iter().any(|id| id == module_id) {
+                module_ids.push(module_id.to_string());
+            }
+        }
         count += 1;
     }
 
+    progress::recompute_after_import(&conn, &module_ids)?;
+
     Ok(format!("{} content blocks saved successfully", count))
 }
 
@@ -208,17 +264,12 @@ pub fn get_module_content(db_path: String, module_id: String) -> Result<String,
         .map_err(|e| format!("Module not found: {}", e))?;
     println!("📦 Found course_id: {}", course_id);
 
-    // ✅ STEP 2: Get the enrollment_id for this course
+    // ✅ Refuse content for lapsed offline sessions and locked modules
+    access::ensure_module_access(&conn, &module_id)?;
+
+    // ✅ STEP 2: Get the signed-in learner's enrollment for this course
     println!("👤 STEP 2: Getting enrollment_id...");
-    let enrollment_id: Result<String, rusqlite::Error> = conn.query_row(
-        "SELECT e.id FROM enrollments e
-         JOIN users u ON e.student_id = u.id
-         WHERE e.course_id = ?1
-         ORDER BY e.created_at DESC
-         LIMIT 1",
-        params![course_id],
-        |row| row.get(0),
-    );
+    let enrollment_id = access::require_enrollment_id(&conn, &course_id);
 
     if let Ok(ref enroll_id) = enrollment_id {
         println!("👤 Found enrollment_id: {}", enroll_id);
@@ -382,6 +433,15 @@ pub fn get_content_block_by_id(db_path: String, content_id: String) -> Result<St
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    let module_id: String = conn
+        .query_row(
+            "SELECT module_id FROM content_blocks WHERE id = ?1",
+            params![content_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Content block not found: {}", e))?;
+    access::ensure_module_access(&conn, &module_id)?;
+
     let content_json: String = conn
         .query_row(
             "SELECT json_object(
@@ -419,12 +479,31 @@ pub fn save_quiz(db_path: String, quiz_data: String) -> Result<String, String> {
     let updated_at = quiz["updated_at"].as_str().unwrap_or(&now);
 
     // ✅ REMOVED student-specific fields - they're calculated at runtime from quiz_attempts
+    // ✅ Upsert so re-saving a quiz keeps its questions and attempts
     conn.execute(
-        "INSERT OR REPLACE INTO quizzes
+        "INSERT INTO quizzes
          (id, title, description, quiz_type, module_id, course_id, time_limit_minutes,
           pass_mark_percentage, max_attempts, attempt_reset_hours, shuffle_questions,
-          question_count, created_at, updated_at, last_synced_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, datetime('now'))",
+          question_count, created_at, updated_at, shuffle_options, show_correct_answers,
+          last_synced_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, datetime('now'))
+         ON CONFLICT(id) DO UPDATE SET
+            title = excluded.title,
+            description = excluded.description,
+            quiz_type = excluded.quiz_type,
+            module_id = excluded.module_id,
+            course_id = excluded.course_id,
+            time_limit_minutes = excluded.time_limit_minutes,
+            pass_mark_percentage = excluded.pass_mark_percentage,
+            max_attempts = excluded.max_attempts,
+            attempt_reset_hours = excluded.attempt_reset_hours,
+            shuffle_questions = excluded.shuffle_questions,
+            question_count = excluded.question_count,
+            created_at = excluded.created_at,
+            updated_at = excluded.updated_at,
+            shuffle_options = excluded.shuffle_options,
+            show_correct_answers = excluded.show_correct_answers,
+            last_synced_at = excluded.last_synced_at",
         params![
             quiz["id"].as_str(),
             quiz["title"].as_str(),
@@ -440,10 +519,16 @@ pub fn save_quiz(db_path: String, quiz_data: String) -> Result<String, String> {
             quiz["question_count"].as_i64(),
             created_at,
             updated_at,
+            quiz["shuffle_options"].as_bool().unwrap_or(false),
+            quiz["show_correct_answers"].as_bool().unwrap_or(true),
         ],
     )
     .map_err(|e| format!("Failed to save quiz: {}", e))?;
 
+    if let Some(quiz_id) = quiz["id"].as_str() {
+        search::index_quiz(&conn, quiz_id)?;
+    }
+
     Ok("Quiz saved successfully".to_string())
 }
 
@@ -467,6 +552,8 @@ pub fn get_module_quiz(db_path: String, module_id: String) -> Result<String, Str
                 'max_attempts', max_attempts,
                 'attempt_reset_hours', attempt_reset_hours,
                 'shuffle_questions', shuffle_questions,
+                'shuffle_options', shuffle_options,
+                'show_correct_answers', show_correct_answers,
                 'question_count', question_count,
                 'created_at', created_at,
                 'updated_at', updated_at
@@ -499,6 +586,8 @@ pub fn get_quiz_by_id(db_path: String, quiz_id: String) -> Result<String, String
                 'max_attempts', max_attempts,
                 'attempt_reset_hours', attempt_reset_hours,
                 'shuffle_questions', shuffle_questions,
+                'shuffle_options', shuffle_options,
+                'show_correct_answers', show_correct_answers,
                 'question_count', question_count,
                 'created_at', created_at,
                 'updated_at', updated_at
@@ -531,6 +620,8 @@ pub fn get_course_final_exam(db_path: String, course_id: String) -> Result<Strin
                 'max_attempts', max_attempts,
                 'attempt_reset_hours', attempt_reset_hours,
                 'shuffle_questions', shuffle_questions,
+                'shuffle_options', shuffle_options,
+                'show_correct_answers', show_correct_answers,
                 'question_count', question_count,
                 'created_at', created_at,
                 'updated_at', updated_at
@@ -549,25 +640,48 @@ pub fn get_course_final_exam(db_path: String, course_id: String) -> Result<Strin
 // QUESTION COMMANDS
 // ============================================================================
 
-#[tauri::command]
-pub fn save_question(db_path: String, question_data: String) -> Result<String, String> {
-    let conn = get_connection(&db_path)
-        .map_err(|e| format!("Database connection failed: {}", e))?;
-
-    let question: JsonValue = serde_json::from_str(&question_data)
-        .map_err(|e| format!("Invalid JSON: {}", e))?;
+// Shared by save_question and save_questions_bulk
+fn save_question_row(conn: &Connection, question: &JsonValue, now: &str) -> Result<(), String> {
+    let question_id = question["id"].as_str().ok_or("Missing question id")?;
 
     // Generate timestamps if not provided
-    let now = chrono::Utc::now().to_rfc3339();
-    let created_at = question["created_at"].as_str().unwrap_or(&now);
-    let updated_at = question["updated_at"].as_str().unwrap_or(&now);
+    let created_at = question["created_at"].as_str().unwrap_or(now);
+    let updated_at = question["updated_at"].as_str().unwrap_or(now);
+
+    let question_type = question["question_type"]
+        .as_str()
+        .or(question["type"].as_str())
+        .unwrap_or("single_choice");
+
+    if !assessment::QUESTION_TYPES.contains(&question_type) {
+        return Err(format!(
+            "Unsupported question type '{}' for question {}",
+            question_type, question_id
+        ));
+    }
+
+    // ✅ Upserts keep the answers learners already gave to this question
+    let accepted_answers = question["accepted_answers"]
+        .as_array()
+        .map(|answers| JsonValue::Array(answers.clone()).to_string());
 
     conn.execute(
-        "INSERT OR REPLACE INTO questions
-         (id, quiz_id, question_text, image_url, order_index, points, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        "INSERT INTO questions
+         (id, quiz_id, question_text, image_url, order_index, points, created_at, updated_at,
+          question_type, accepted_answers)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+         ON CONFLICT(id) DO UPDATE SET
+            quiz_id = excluded.quiz_id,
+            question_text = excluded.question_text,
+            image_url = excluded.image_url,
+            order_index = excluded.order_index,
+            points = excluded.points,
+            created_at = excluded.created_at,
+            updated_at = excluded.updated_at,
+            question_type = excluded.question_type,
+            accepted_answers = excluded.accepted_answers",
         params![
-            question["id"].as_str(),
+            question_id,
             question["quiz_id"].as_str(),
             question["question_text"].as_str(),
             question["image_url"].as_str(),
@@ -575,29 +689,69 @@ pub fn save_question(db_path: String, question_data: String) -> Result<String, S
             question["points"].as_f64(),
             created_at,
             updated_at,
+            question_type,
+            accepted_answers,
         ],
     )
     .map_err(|e| format!("Failed to save question: {}", e))?;
 
-    // Save options if provided
-    if let Some(options) = question["options"].as_array() {
-        for option in options {
-            conn.execute(
-                "INSERT OR REPLACE INTO question_options
-                 (id, question_id, option_text, is_correct, order_index)
-                 VALUES (?1, ?2, ?3, ?4, ?5)",
-                params![
-                    option["id"].as_str(),
-                    question["id"].as_str(),
-                    option["option_text"].as_str(),
-                    option["is_correct"].as_bool(),
-                    option["order"].as_i64().or(option["order_index"].as_i64()),
-                ],
-            )
-            .map_err(|e| format!("Failed to save option: {}", e))?;
+    let mut options = question["options"].as_array().cloned().unwrap_or_default();
+
+    // True/false questions may arrive as a bare correct_answer flag
+    if question_type == "true_false" && options.is_empty() {
+        if let Some(answer) = question["correct_answer"].as_bool() {
+            options = vec![
+                serde_json::json!({
+                    "id": format!("{}_true", question_id),
+                    "option_text": "True",
+                    "is_correct": answer,
+                    "order": 1
+                }),
+                serde_json::json!({
+                    "id": format!("{}_false", question_id),
+                    "option_text": "False",
+                    "is_correct": !answer,
+                    "order": 2
+                }),
+            ];
         }
     }
 
+    for option in options {
+        conn.execute(
+            "INSERT INTO question_options
+             (id, question_id, option_text, is_correct, order_index)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(id) DO UPDATE SET
+                question_id = excluded.question_id,
+                option_text = excluded.option_text,
+                is_correct = excluded.is_correct,
+                order_index = excluded.order_index",
+            params![
+                option["id"].as_str(),
+                question_id,
+                option["option_text"].as_str(),
+                option["is_correct"].as_bool(),
+                option["order"].as_i64().or(option["order_index"].as_i64()),
+            ],
+        )
+        .map_err(|e| format!("Failed to save option: {}", e))?;
+    }
+
+    search::index_question(conn, question_id)
+}
+
+#[tauri::command]
+pub fn save_question(db_path: String, question_data: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let question: JsonValue = serde_json::from_str(&question_data)
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let now = chrono::Utc::now().to_rfc3339();
+    save_question_row(&conn, &question, &now)?;
+
     Ok("Question saved successfully".to_string())
 }
 
@@ -613,62 +767,29 @@ pub fn save_questions_bulk(db_path: String, questions_data: String) -> Result<St
     let mut count = 0;
 
     for question in questions {
-        // Generate timestamps if not provided
-        let created_at = question["created_at"].as_str().unwrap_or(&now);
-        let updated_at = question["updated_at"].as_str().unwrap_or(&now);
-
-        conn.execute(
-            "INSERT OR REPLACE INTO questions
-             (id, quiz_id, question_text, image_url, order_index, points, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            params![
-                question["id"].as_str(),
-                question["quiz_id"].as_str(),
-                question["question_text"].as_str(),
-                question["image_url"].as_str(),
-                question["order"].as_i64().or(question["order_index"].as_i64()),
-                question["points"].as_f64(),
-                created_at,
-                updated_at,
-            ],
-        )
-        .map_err(|e| format!("Failed to save question: {}", e))?;
-
-        // Save options
-        if let Some(options) = question["options"].as_array() {
-            for option in options {
-                conn.execute(
-                    "INSERT OR REPLACE INTO question_options
-                     (id, question_id, option_text, is_correct, order_index)
-                     VALUES (?1, ?2, ?3, ?4, ?5)",
-                    params![
-                        option["id"].as_str(),
-                        question["id"].as_str(),
-                        option["option_text"].as_str(),
-                        option["is_correct"].as_bool(),
-                        option["order"].as_i64().or(option["order_index"].as_i64()),
-                    ],
-                )
-                .map_err(|e| format!("Failed to save option: {}", e))?;
-            }
-        }
+        save_question_row(&conn, &question, &now)?;
         count += 1;
     }
 
     Ok(format!("{} questions saved successfully", count))
 }
 
+// ✅ Learner-facing: option correctness is never sent to the webview, and
+// short-answer questions carry no options since those are the accepted answers
 #[tauri::command]
 pub fn get_quiz_questions(db_path: String, quiz_id: String) -> Result<String, String> {
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    access::ensure_quiz_access(&conn, &quiz_id)?;
+
     let mut stmt = conn
         .prepare(
             "SELECT json_object(
                 'id', q.id,
                 'quiz_id', q.quiz_id,
                 'question_text', q.question_text,
+                'question_type', q.question_type,
                 'image_url', q.image_url,
                 'order', q.order_index,
                 'points', q.points,
@@ -678,12 +799,12 @@ pub fn get_quiz_questions(db_path: String, quiz_id: String) -> Result<String, St
                             'id', o.id,
                             'question_id', o.question_id,
                             'option_text', o.option_text,
-                            'is_correct', o.is_correct,
                             'order', o.order_index
                         )
                     )
                     FROM question_options o
                     WHERE o.question_id = q.id
+                      AND COALESCE(q.question_type, 'single_choice') != 'short_answer'
                     ORDER BY o.order_index
                 )
              ) FROM questions q
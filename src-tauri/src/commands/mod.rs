@@ -1,13 +1,46 @@
+pub mod access;
+pub mod activity;
+pub mod annotations;
+pub mod assessment;
 pub mod auth;
+pub mod certificates;
 pub mod courses;
 pub mod lessons;
+pub mod profiles;
 pub mod progress;
+pub mod review;
+pub mod search;
 pub mod offline;
 pub mod sync;
 
 use rusqlite::{Connection, Result as SqliteResult};
 
-// Helper function used by all command modules
+// Helper function used by all command modules. Foreign keys are switched on
+// here rather than left to how SQLite happened to be built.
 pub fn get_connection(db_path: &str) -> SqliteResult<Connection> {
-    Connection::open(db_path)
+    let conn = Connection::open(db_path)?;
+    conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+    Ok(conn)
+}
+
+// Runs a bulk removal with foreign key enforcement off, so cascades cannot
+// reach rows the caller means to keep. The previous setting is restored
+// whether or not the removal succeeds.
+pub fn without_foreign_keys<T>(
+    conn: &mut Connection,
+    removal: impl FnOnce(&mut Connection) -> Result<T, String>,
+) -> Result<T, String> {
+    let enabled: bool = conn
+        .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to read foreign key setting: {}", e))?;
+
+    conn.execute_batch("PRAGMA foreign_keys = OFF;")
+        .map_err(|e| format!("Failed to disable foreign keys: {}", e))?;
+
+    let result = removal(conn);
+
+    conn.execute_batch(if enabled { "PRAGMA foreign_keys = ON;" } else { "PRAGMA foreign_keys = OFF;" })
+        .map_err(|e| format!("Failed to restore foreign keys: {}", e))?;
+
+    result
 }
@@ -1,4 +1,5 @@
-use crate::commands::get_connection;
+use crate::commands::{access, activity, get_connection, search, without_foreign_keys};
+use crate::{media, media_crypto};
 use rusqlite::params;
 use serde_json::Value as JsonValue;
 
@@ -14,12 +15,29 @@ pub fn save_offline_session(db_path: String, session_data: String) -> Result<Str
     let session: JsonValue = serde_json::from_str(&session_data)
         .map_err(|e| format!("Invalid JSON: {}", e))?;
 
+    // ✅ Upsert rather than REPLACE: a REPLACE deletes the row first, and with
+    // foreign keys on that takes the session's media key and unsynced progress
+    // batches with it
     conn.execute(
-        "INSERT OR REPLACE INTO offline_sessions
+        "INSERT INTO offline_sessions
          (id, student_id, course_id, downloaded_at, expires_at, package_version,
           presigned_url_expiry_days, last_synced_at, sync_count, is_deleted,
-          created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+          created_at, updated_at, total_media_size_bytes, grace_period_hours)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+         ON CONFLICT(id) DO UPDATE SET
+            student_id = excluded.student_id,
+            course_id = excluded.course_id,
+            downloaded_at = excluded.downloaded_at,
+            expires_at = excluded.expires_at,
+            package_version = excluded.package_version,
+            presigned_url_expiry_days = excluded.presigned_url_expiry_days,
+            last_synced_at = excluded.last_synced_at,
+            sync_count = excluded.sync_count,
+            is_deleted = excluded.is_deleted,
+            created_at = excluded.created_at,
+            updated_at = excluded.updated_at,
+            total_media_size_bytes = COALESCE(excluded.total_media_size_bytes, total_media_size_bytes),
+            grace_period_hours = excluded.grace_period_hours",
         params![
             session["id"].as_str(),
             session["student_id"].as_str(),
@@ -33,10 +51,21 @@ pub fn save_offline_session(db_path: String, session_data: String) -> Result<Str
             session["is_deleted"].as_bool().unwrap_or(false),
             session["created_at"].as_str(),
             session["updated_at"].as_str(),
+            session["total_media_size_bytes"].as_i64(),
+            access::clamp_grace_period_hours(session["grace_period_hours"].as_i64()),
         ],
     )
     .map_err(|e| format!("Failed to save offline session: {}", e))?;
 
+    // ✅ Every session gets a media key; re-saving keeps the existing one. A
+    // course another profile already downloaded shares that profile's key.
+    if let Some(session_id) = session["id"].as_str() {
+        if let Some(course_id) = session["course_id"].as_str() {
+            media_crypto::share_course_key(&conn, session_id, course_id)?;
+        }
+        media_crypto::ensure_session_key(&conn, session_id)?;
+    }
+
     Ok("Offline session saved successfully".to_string())
 }
 
@@ -60,6 +89,7 @@ pub fn get_offline_session_by_id(db_path: String, session_id: String) -> Result<
                 'is_deleted', os.is_deleted,
                 'created_at', os.created_at,
                 'updated_at', os.updated_at,
+                'total_media_size_bytes', os.total_media_size_bytes,
                 'is_expired', CASE WHEN datetime(os.expires_at) < datetime('now') THEN 1 ELSE 0 END,
                 'is_valid', CASE
                     WHEN os.is_deleted = 1 THEN 0
@@ -102,6 +132,7 @@ pub fn get_student_offline_sessions(
                 'is_deleted', os.is_deleted,
                 'created_at', os.created_at,
                 'updated_at', os.updated_at,
+                'total_media_size_bytes', os.total_media_size_bytes,
                 'is_expired', CASE WHEN datetime(os.expires_at) < datetime('now') THEN 1 ELSE 0 END,
                 'is_valid', CASE
                     WHEN os.is_deleted = 1 THEN 0
@@ -129,6 +160,7 @@ pub fn get_student_offline_sessions(
                 'is_deleted', os.is_deleted,
                 'created_at', os.created_at,
                 'updated_at', os.updated_at,
+                'total_media_size_bytes', os.total_media_size_bytes,
                 'is_expired', CASE WHEN datetime(os.expires_at) < datetime('now') THEN 1 ELSE 0 END,
                 'is_valid', CASE
                     WHEN os.is_deleted = 1 THEN 0
@@ -156,6 +188,7 @@ pub fn get_student_offline_sessions(
                 'is_deleted', os.is_deleted,
                 'created_at', os.created_at,
                 'updated_at', os.updated_at,
+                'total_media_size_bytes', os.total_media_size_bytes,
                 'is_expired', CASE WHEN datetime(os.expires_at) < datetime('now') THEN 1 ELSE 0 END,
                 'is_valid', CASE
                     WHEN os.is_deleted = 1 THEN 0
@@ -183,6 +216,7 @@ pub fn get_student_offline_sessions(
                 'is_deleted', os.is_deleted,
                 'created_at', os.created_at,
                 'updated_at', os.updated_at,
+                'total_media_size_bytes', os.total_media_size_bytes,
                 'is_expired', CASE WHEN datetime(os.expires_at) < datetime('now') THEN 1 ELSE 0 END,
                 'is_valid', CASE
                     WHEN os.is_deleted = 1 THEN 0
@@ -251,6 +285,10 @@ pub fn delete_offline_session(db_path: String, session_id: String) -> Result<Str
     )
     .map_err(|e| format!("Failed to delete offline session: {}", e))?;
 
+    // Without its key the session's downloaded media can no longer be decrypted,
+    // unless another profile's session shares the key
+    media_crypto::revoke_session_key(&conn, &session_id)?;
+
     Ok("Offline session deleted successfully".to_string())
 }
 
@@ -259,6 +297,10 @@ pub fn hard_delete_offline_session(db_path: String, session_id: String) -> Resul
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    // Revoke first so the media is handed over to another profile before the
+    // key row is deleted
+    media_crypto::revoke_session_key(&conn, &session_id)?;
+
     // Hard delete (permanent)
     conn.execute(
         "DELETE FROM offline_sessions WHERE id = ?1",
@@ -317,6 +359,8 @@ pub fn delete_expired_offline_sessions(db_path: String, days_old: i64) -> Result
     )
     .map_err(|e| format!("Failed to delete expired sessions: {}", e))?;
 
+    media_crypto::revoke_orphaned_session_keys(&conn)?;
+
     Ok(count as i64)
 }
 
@@ -332,11 +376,21 @@ pub fn save_media_cache(db_path: String, cache_data: String) -> Result<String, S
     let cache: JsonValue = serde_json::from_str(&cache_data)
         .map_err(|e| format!("Invalid JSON: {}", e))?;
 
+    let is_downloaded = cache["is_downloaded"].as_bool().unwrap_or(false);
+
+    // ✅ Downloaded files are encrypted at rest with their offline session's key
+    let encryption_session_id = if is_downloaded {
+        Some(encrypt_cached_file(&conn, &db_path, &cache)?)
+    } else {
+        None
+    };
+
     conn.execute(
         "INSERT OR REPLACE INTO media_cache
          (media_id, course_id, filename, media_type, local_file_path, size_bytes,
-          downloaded_at, presigned_url, presigned_url_expires_at, is_downloaded, download_progress)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+          downloaded_at, presigned_url, presigned_url_expires_at, is_downloaded, download_progress,
+          encryption_session_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
         params![
             cache["media_id"].as_str(),
             cache["course_id"].as_str(),
@@ -347,8 +401,9 @@ pub fn save_media_cache(db_path: String, cache_data: String) -> Result<String, S
             cache["downloaded_at"].as_str(),
             cache["presigned_url"].as_str(),
             cache["presigned_url_expires_at"].as_str(),
-            cache["is_downloaded"].as_bool().unwrap_or(false),
+            is_downloaded,
             cache["download_progress"].as_i64().unwrap_or(0),
+            encryption_session_id,
         ],
     )
     .map_err(|e| format!("Failed to save media cache: {}", e))?;
@@ -356,11 +411,62 @@ pub fn save_media_cache(db_path: String, cache_data: String) -> Result<String, S
     Ok("Media cache saved successfully".to_string())
 }
 
+// Encrypts a downloaded media file and returns the session whose key was used
+fn encrypt_cached_file(
+    conn: &rusqlite::Connection,
+    db_path: &str,
+    cache: &JsonValue,
+) -> Result<String, String> {
+    let media_id = cache["media_id"].as_str()
+        .ok_or_else(|| "Missing media_id".to_string())?;
+    let course_id = cache["course_id"].as_str()
+        .ok_or_else(|| "Missing course_id".to_string())?;
+    let local_file_path = cache["local_file_path"].as_str()
+        .ok_or_else(|| "Missing local_file_path".to_string())?;
+
+    let path = media::ensure_in_media_store(db_path, local_file_path)?;
+
+    // A file that is already encrypted must keep the session it was encrypted under
+    let already_encrypted = media_crypto::is_encrypted(&path)
+        .map_err(|e| format!("Failed to read media file: {}", e))?;
+    if already_encrypted {
+        return conn
+            .query_row(
+                "SELECT encryption_session_id FROM media_cache
+                 WHERE media_id = ?1 AND encryption_session_id IS NOT NULL",
+                params![media_id],
+                |row| row.get(0),
+            )
+            .map_err(|_| format!("Encrypted media {} has no known session key", media_id));
+    }
+
+    let session_id: String = match cache["session_id"].as_str() {
+        Some(id) => id.to_string(),
+        None => conn
+            .query_row(
+                "SELECT id FROM offline_sessions
+                 WHERE course_id = ?1 AND is_deleted = 0
+                 ORDER BY downloaded_at DESC
+                 LIMIT 1",
+                params![course_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("No offline session found for course {}: {}", course_id, e))?,
+    };
+
+    let key = media_crypto::ensure_session_key(conn, &session_id)?;
+    media_crypto::encrypt_file_in_place(&path, &key)?;
+
+    Ok(session_id)
+}
+
 #[tauri::command]
 pub fn get_media_cache_by_course(db_path: String, course_id: String) -> Result<String, String> {
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    access::ensure_offline_access(&conn, &course_id)?;
+
     let mut stmt = conn
         .prepare(
             "SELECT json_object(
@@ -375,7 +481,8 @@ pub fn get_media_cache_by_course(db_path: String, course_id: String) -> Result<S
                 'presigned_url', presigned_url,
                 'presigned_url_expires_at', presigned_url_expires_at,
                 'is_downloaded', is_downloaded,
-                'download_progress', download_progress
+                'download_progress', download_progress,
+                'is_encrypted', encryption_session_id IS NOT NULL
              ) FROM media_cache
              WHERE course_id = ?1
              ORDER BY downloaded_at DESC",
@@ -397,6 +504,15 @@ pub fn get_media_cache_by_media_id(db_path: String, media_id: String) -> Result<
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    let course_id: String = conn
+        .query_row(
+            "SELECT course_id FROM media_cache WHERE media_id = ?1",
+            params![media_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Media cache not found: {}", e))?;
+    access::ensure_offline_access(&conn, &course_id)?;
+
     let cache_json: String = conn
         .query_row(
             "SELECT json_object(
@@ -411,7 +527,8 @@ pub fn get_media_cache_by_media_id(db_path: String, media_id: String) -> Result<
                 'presigned_url', presigned_url,
                 'presigned_url_expires_at', presigned_url_expires_at,
                 'is_downloaded', is_downloaded,
-                'download_progress', download_progress
+                'download_progress', download_progress,
+                'is_encrypted', encryption_session_id IS NOT NULL
              ) FROM media_cache WHERE media_id = ?1",
             params![media_id],
             |row| row.get(0),
@@ -447,13 +564,329 @@ pub fn delete_media_cache_by_course(db_path: String, course_id: String) -> Resul
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    let media_files = course_media_files(&conn, &course_id)?;
+
     conn.execute(
         "DELETE FROM media_cache WHERE course_id = ?1",
         params![course_id],
     )
     .map_err(|e| format!("Failed to delete media cache: {}", e))?;
 
-    Ok("Media cache deleted successfully".to_string())
+    // ✅ Remove the files too, not just the rows that point at them
+    let removal = delete_course_media_files(&db_path, &course_id, media_files)?;
+
+    Ok(format!(
+        "Media cache deleted successfully ({} files, {} bytes freed)",
+        removal.files_deleted, removal.bytes_freed
+    ))
+}
+
+struct MediaRemoval {
+    files_deleted: i64,
+    bytes_freed: i64,
+    warnings: Vec<String>,
+}
+
+// The course's cached media files as (media_id, local_file_path)
+fn course_media_files(
+    conn: &rusqlite::Connection,
+    course_id: &str,
+) -> Result<Vec<(String, String)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT media_id, local_file_path FROM media_cache WHERE course_id = ?1")
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let files = stmt
+        .query_map(params![course_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(files)
+}
+
+// Deletes the course's cached media files from disk once their rows are gone.
+// Paths outside the media store are never touched.
+fn delete_course_media_files(
+    db_path: &str,
+    course_id: &str,
+    files: Vec<(String, String)>,
+) -> Result<MediaRemoval, String> {
+    let mut removal = MediaRemoval {
+        files_deleted: 0,
+        bytes_freed: 0,
+        warnings: Vec::new(),
+    };
+
+    for (media_id, local_file_path) in files {
+        if !std::path::Path::new(&local_file_path).exists() {
+            continue;
+        }
+
+        let path = match media::ensure_in_media_store(db_path, &local_file_path) {
+            Ok(path) => path,
+            Err(e) => {
+                removal.warnings.push(format!("Skipped media {}: {}", media_id, e));
+                continue;
+            }
+        };
+
+        let size = std::fs::metadata(&path).map(|m| m.len() as i64).unwrap_or(0);
+        match std::fs::remove_file(&path) {
+            Ok(()) => {
+                removal.files_deleted += 1;
+                removal.bytes_freed += size;
+            }
+            Err(e) => removal
+                .warnings
+                .push(format!("Failed to delete media {}: {}", media_id, e)),
+        }
+    }
+
+    // Clean up the course's media directory once its files are gone
+    let is_plain_id = !course_id.is_empty()
+        && !course_id.contains(['/', '\\'])
+        && course_id != "."
+        && course_id != "..";
+    if is_plain_id {
+        let course_dir = media::get_media_store_dir(db_path)?.join(course_id);
+        if course_dir.is_dir() {
+            if let Err(e) = std::fs::remove_dir_all(&course_dir) {
+                removal
+                    .warnings
+                    .push(format!("Failed to remove course media directory: {}", e));
+            }
+        }
+    }
+
+    Ok(removal)
+}
+
+// ============================================================================
+// COURSE REMOVAL
+// ============================================================================
+
+// Quizzes belonging to a course: final exams carry course_id, module quizzes module_id
+const COURSE_QUIZ_IDS: &str = "SELECT id FROM quizzes
+     WHERE course_id = ?1 OR module_id IN (SELECT id FROM modules WHERE course_id = ?1)";
+
+// Counts progress for a course that has not reached the server yet:
+// (unsynced offline batches, pending sync_queue entries)
+pub fn count_unsynced_course_data(
+    conn: &rusqlite::Connection,
+    course_id: &str,
+) -> Result<(i64, i64), String> {
+    let unsynced_batches: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM offline_progress_batch WHERE course_id = ?1 AND synced = 0",
+            params![course_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to count unsynced batches: {}", e))?;
+
+    let pending_sync_items: i64 = conn
+        .query_row(
+            &format!(
+                "SELECT COUNT(*) FROM sync_queue
+                 WHERE CASE WHEN json_valid(data) THEN json_extract(data, '$.course_id') END = ?1
+                    OR record_id IN (SELECT id FROM enrollments WHERE course_id = ?1)
+                    OR record_id IN (
+                        SELECT mp.id FROM module_progress mp
+                        JOIN modules m ON mp.module_id = m.id
+                        WHERE m.course_id = ?1
+                    )
+                    OR record_id IN (
+                        SELECT cp.id FROM content_progress cp
+                        JOIN content_blocks cb ON cp.content_id = cb.id
+                        JOIN modules m ON cb.module_id = m.id
+                        WHERE m.course_id = ?1
+                    )
+                    OR record_id IN (SELECT id FROM quiz_attempts WHERE quiz_id IN ({}))",
+                COURSE_QUIZ_IDS
+            ),
+            params![course_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to count pending sync items: {}", e))?;
+
+    Ok((unsynced_batches, pending_sync_items))
+}
+
+#[tauri::command]
+pub fn remove_course_from_device(
+    db_path: String,
+    course_id: String,
+    keep_progress: bool,
+) -> Result<String, String> {
+    let mut conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let (unsynced_batches, pending_sync_items) = count_unsynced_course_data(&conn, &course_id)?;
+    let has_unsynced = unsynced_batches > 0 || pending_sync_items > 0;
+
+    // ✅ Never throw away progress the server has not seen
+    if has_unsynced && !keep_progress {
+        return Err(format!(
+            "Course has unsynced progress ({} offline batches, {} queued changes). \
+             Sync first or keep progress when removing the course.",
+            unsynced_batches, pending_sync_items
+        ));
+    }
+
+    let mut warnings: Vec<String> = Vec::new();
+    if has_unsynced {
+        warnings.push(format!(
+            "Kept {} unsynced offline batches and {} queued changes for the next sync",
+            unsynced_batches, pending_sync_items
+        ));
+    }
+
+    // Files go only once the rows are gone for good, so a failed removal
+    // leaves a course whose media still plays
+    let media_files = course_media_files(&conn, &course_id)?;
+
+    // Cascades would otherwise take progress down with the content it points at;
+    // every table is cleared explicitly below instead.
+    let (progress_rows, media_rows, content_rows, session_rows) =
+        without_foreign_keys(&mut conn, |conn| {
+            let tx = conn
+                .transaction()
+                .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+            let delete = |label: &str, sql: &str| -> Result<usize, String> {
+                tx.execute(sql, params![course_id])
+                    .map_err(|e| format!("Failed to delete {}: {}", label, e))
+            };
+
+            let mut progress_rows = 0;
+            if !keep_progress {
+                progress_rows += delete(
+                    "quiz answers",
+                    &format!(
+                        "DELETE FROM quiz_answers WHERE attempt_id IN (
+                            SELECT id FROM quiz_attempts WHERE quiz_id IN ({})
+                         )",
+                        COURSE_QUIZ_IDS
+                    ),
+                )?;
+                progress_rows += delete(
+                    "attempt question order",
+                    &format!(
+                        "DELETE FROM attempt_question_order WHERE attempt_id IN (
+                            SELECT id FROM quiz_attempts WHERE quiz_id IN ({})
+                         )",
+                        COURSE_QUIZ_IDS
+                    ),
+                )?;
+                progress_rows += delete(
+                    "quiz attempts",
+                    &format!("DELETE FROM quiz_attempts WHERE quiz_id IN ({})", COURSE_QUIZ_IDS),
+                )?;
+                progress_rows += delete(
+                    "content progress",
+                    "DELETE FROM content_progress
+                     WHERE enrollment_id IN (SELECT id FROM enrollments WHERE course_id = ?1)",
+                )?;
+                progress_rows += delete(
+                    "module progress",
+                    "DELETE FROM module_progress
+                     WHERE enrollment_id IN (SELECT id FROM enrollments WHERE course_id = ?1)",
+                )?;
+                progress_rows += delete(
+                    "review cards",
+                    "DELETE FROM review_cards WHERE course_id = ?1",
+                )?;
+                progress_rows += delete(
+                    "resume positions",
+                    "DELETE FROM resume_positions
+                     WHERE enrollment_id IN (SELECT id FROM enrollments WHERE course_id = ?1)",
+                )?;
+                progress_rows += delete(
+                    "learning sessions",
+                    "DELETE FROM learning_sessions WHERE course_id = ?1",
+                )?;
+                progress_rows += delete("bookmarks", "DELETE FROM bookmarks WHERE course_id = ?1")?;
+                progress_rows += delete("notes", "DELETE FROM notes WHERE course_id = ?1")?;
+            }
+
+            let media_rows = delete("media cache", "DELETE FROM media_cache WHERE course_id = ?1")?;
+
+            search::remove_course_content(&tx, &course_id)?;
+
+            let mut content_rows = delete(
+                "question options",
+                &format!(
+                    "DELETE FROM question_options WHERE question_id IN (
+                        SELECT id FROM questions WHERE quiz_id IN ({})
+                     )",
+                    COURSE_QUIZ_IDS
+                ),
+            )?;
+            content_rows += delete(
+                "questions",
+                &format!("DELETE FROM questions WHERE quiz_id IN ({})", COURSE_QUIZ_IDS),
+            )?;
+            content_rows += delete(
+                "quizzes",
+                &format!("DELETE FROM quizzes WHERE id IN ({})", COURSE_QUIZ_IDS),
+            )?;
+            content_rows += delete(
+                "content blocks",
+                "DELETE FROM content_blocks
+                 WHERE module_id IN (SELECT id FROM modules WHERE course_id = ?1)",
+            )?;
+            content_rows += delete("modules", "DELETE FROM modules WHERE course_id = ?1")?;
+
+            // Revoke media keys, then drop the sessions. Sessions that still own
+            // unsynced batches are only soft-deleted so the batches survive.
+            delete(
+                "media keys",
+                "DELETE FROM offline_session_keys
+                 WHERE session_id IN (SELECT id FROM offline_sessions WHERE course_id = ?1)",
+            )?;
+            delete(
+                "synced progress batches",
+                "DELETE FROM offline_progress_batch WHERE course_id = ?1 AND synced = 1",
+            )?;
+            let now = chrono::Utc::now().to_rfc3339();
+            tx.execute(
+                "UPDATE offline_sessions SET is_deleted = 1, updated_at = ?2
+                 WHERE course_id = ?1
+                   AND id IN (SELECT session_id FROM offline_progress_batch WHERE synced = 0)",
+                params![course_id, now],
+            )
+            .map_err(|e| format!("Failed to delete offline sessions: {}", e))?;
+            let session_rows = delete(
+                "offline sessions",
+                "DELETE FROM offline_sessions
+                 WHERE course_id = ?1
+                   AND id NOT IN (SELECT session_id FROM offline_progress_batch WHERE synced = 0)",
+            )?;
+
+            tx.commit()
+                .map_err(|e| format!("Failed to commit course removal: {}", e))?;
+
+            Ok((progress_rows, media_rows, content_rows, session_rows))
+        })?;
+
+    let removal = delete_course_media_files(&db_path, &course_id, media_files)?;
+    warnings.extend(removal.warnings);
+
+    let report = serde_json::json!({
+        "course_id": course_id,
+        "keep_progress": keep_progress,
+        "files_deleted": removal.files_deleted,
+        "bytes_freed": removal.bytes_freed,
+        "media_cache_rows_deleted": media_rows,
+        "content_rows_deleted": content_rows,
+        "progress_rows_deleted": progress_rows,
+        "offline_sessions_deleted": session_rows,
+        "unsynced_batches": unsynced_batches,
+        "pending_sync_items": pending_sync_items,
+        "warnings": warnings
+    });
+
+    Ok(report.to_string())
 }
 
 // ============================================================================
@@ -483,6 +916,24 @@ pub fn save_offline_progress_batch(db_path: String, batch_data: String) -> Resul
     )
     .map_err(|e| format!("Failed to save progress batch: {}", e))?;
 
+    // ✅ Report the time studied since the previous batch, unless the caller did
+    if let (Some(course_id), Some(session_id)) =
+        (batch["course_id"].as_str(), batch["session_id"].as_str())
+    {
+        let batch_id = conn.last_insert_rowid();
+        let seconds = activity::assign_unreported_time(&conn, course_id, session_id, batch_id)?;
+
+        if batch["batch_data"]["total_time_spent_seconds"].is_null() {
+            conn.execute(
+                "UPDATE offline_progress_batch
+                 SET batch_data = json_set(batch_data, '$.total_time_spent_seconds', ?1)
+                 WHERE id = ?2 AND json_type(batch_data) = 'object'",
+                params![seconds, batch_id],
+            )
+            .map_err(|e| format!("Failed to save progress batch: {}", e))?;
+        }
+    }
+
     Ok("Offline progress batch saved successfully".to_string())
 }
 
@@ -576,3 +1027,187 @@ pub fn get_offline_session_statistics(db_path: String) -> Result<String, String>
 
     Ok(stats_json)
 }
+
+#[tauri::command]
+pub fn get_course_storage_report(
+    db_path: String,
+    course_id: Option<String>,
+) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    // Every course with cached media or a live offline session
+    let course_ids: Vec<String> = match course_id {
+        Some(cid) => vec![cid],
+        None => {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT course_id FROM media_cache
+                     UNION
+                     SELECT course_id FROM offline_sessions WHERE is_deleted = 0",
+                )
+                .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+            let ids: Vec<String> = stmt
+                .query_map([], |row| row.get(0))
+                .map_err(|e| format!("Query failed: {}", e))?
+                .filter_map(|r| r.ok())
+                .collect();
+            ids
+        }
+    };
+
+    let mut reports = Vec::new();
+    for course_id in course_ids {
+        reports.push(build_course_storage_report(&conn, &course_id)?);
+    }
+
+    Ok(JsonValue::Array(reports).to_string())
+}
+
+fn build_course_storage_report(
+    conn: &rusqlite::Connection,
+    course_id: &str,
+) -> Result<JsonValue, String> {
+    let course_title: Option<String> = conn
+        .query_row(
+            "SELECT title FROM courses WHERE id = ?1",
+            params![course_id],
+            |row| row.get(0),
+        )
+        .ok();
+
+    let session: Option<JsonValue> = conn
+        .query_row(
+            &format!(
+                "SELECT json_object(
+                    'id', id,
+                    'student_id', student_id,
+                    'downloaded_at', downloaded_at,
+                    'expires_at', expires_at,
+                    'last_synced_at', last_synced_at,
+                    'total_media_size_bytes', total_media_size_bytes,
+                    'is_expired', CASE WHEN datetime(expires_at) < datetime('now') THEN 1 ELSE 0 END,
+                    'in_grace_period', CASE
+                        WHEN datetime(expires_at) < datetime('now')
+                         AND {} >= datetime('now') THEN 1
+                        ELSE 0
+                    END,
+                    'days_remaining', CAST(julianday(expires_at) - julianday('now') AS INTEGER)
+                 ) FROM offline_sessions
+                 WHERE course_id = ?1 AND is_deleted = 0
+                 ORDER BY datetime(expires_at) DESC
+                 LIMIT 1",
+                access::session_usable_until("offline_sessions")
+            ),
+            params![course_id],
+            |row| row.get::<_, String>(0),
+        )
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok());
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT media_id, filename, media_type, local_file_path, size_bytes, is_downloaded
+             FROM media_cache WHERE course_id = ?1
+             ORDER BY filename ASC",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let media: Vec<(String, String, String, String, Option<i64>, bool)> = stmt
+        .query_map(params![course_id], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get::<_, Option<bool>>(5)?.unwrap_or(false),
+            ))
+        })
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut files = Vec::new();
+    let (mut complete, mut partial, mut missing) = (0, 0, 0);
+    let (mut bytes_on_disk, mut downloaded_bytes, mut cached_expected_bytes) = (0i64, 0i64, 0i64);
+
+    for (media_id, filename, media_type, local_file_path, size_bytes, is_downloaded) in media {
+        let path = std::path::Path::new(&local_file_path);
+        let disk_size = std::fs::metadata(path).map(|m| m.len() as i64).ok();
+        // Encrypted files carry a small header on top of the original media
+        let media_size = media_crypto::plaintext_size(path).map(|s| s as i64).ok();
+
+        let status = match (disk_size, media_size) {
+            (None, _) | (_, None) => "missing",
+            (Some(_), Some(actual)) => {
+                if is_downloaded && size_bytes.map_or(true, |expected| actual >= expected) {
+                    "complete"
+                } else {
+                    "partial"
+                }
+            }
+        };
+
+        match status {
+            "complete" => complete += 1,
+            "partial" => partial += 1,
+            _ => missing += 1,
+        }
+
+        bytes_on_disk += disk_size.unwrap_or(0);
+        downloaded_bytes += media_size.unwrap_or(0);
+        cached_expected_bytes += size_bytes.unwrap_or(0);
+
+        files.push(serde_json::json!({
+            "media_id": media_id,
+            "filename": filename,
+            "media_type": media_type,
+            "status": status,
+            "expected_bytes": size_bytes,
+            "bytes_on_disk": disk_size.unwrap_or(0)
+        }));
+    }
+
+    // Prefer the package size from the server; fall back to what the cache expects
+    let expected_bytes = session
+        .as_ref()
+        .and_then(|s| s["total_media_size_bytes"].as_i64())
+        .unwrap_or(cached_expected_bytes);
+
+    let (unsynced_batches, pending_sync_items) = count_unsynced_course_data(conn, course_id)?;
+
+    let oldest_unsynced_progress_at: Option<String> = conn
+        .query_row(
+            "SELECT MIN(created_at) FROM offline_progress_batch
+             WHERE course_id = ?1 AND synced = 0",
+            params![course_id],
+            |row| row.get(0),
+        )
+        .unwrap_or(None);
+
+    let download_percentage = if expected_bytes > 0 {
+        ((downloaded_bytes.min(expected_bytes) as f64 / expected_bytes as f64) * 100.0).round()
+    } else {
+        0.0
+    };
+
+    Ok(serde_json::json!({
+        "course_id": course_id,
+        "course_title": course_title,
+        "expected_bytes": expected_bytes,
+        "bytes_on_disk": bytes_on_disk,
+        "downloaded_bytes": downloaded_bytes,
+        "download_percentage": download_percentage,
+        "files_total": complete + partial + missing,
+        "files_complete": complete,
+        "files_partial": partial,
+        "files_missing": missing,
+        "session": session,
+        "unsynced_batches": unsynced_batches,
+        "pending_sync_items": pending_sync_items,
+        "oldest_unsynced_progress_at": oldest_unsynced_progress_at,
+        "files": files
+    }))
+}
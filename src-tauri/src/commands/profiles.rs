@@ -0,0 +1,333 @@
+use crate::commands::{access, activity, auth, get_connection, without_foreign_keys};
+use crate::media_crypto;
+use rusqlite::{params, Connection};
+use std::fs;
+
+// ============================================================================
+// LEARNER PROFILES
+// ============================================================================
+// A shared device keeps every learner who signed in on it. Profiles own their
+// tokens and progress; course content and downloaded media are stored once
+// and shared by all of them.
+
+const PROFILE_JSON: &str = "json_object(
+    'id', u.id,
+    'email', u.email,
+    'first_name', u.first_name,
+    'last_name', u.last_name,
+    'full_name', u.full_name,
+    'role', u.role,
+    'profile_image_url', u.profile_image_url,
+    'last_signed_in_at', u.last_signed_in_at,
+    'is_active_profile', u.id IS (SELECT value FROM app_metadata WHERE key = 'active_user_id'),
+    'can_sign_in_offline', u.offline_credential IS NOT NULL,
+    'has_saved_login', EXISTS(
+        SELECT 1 FROM auth_tokens t WHERE t.user_id = u.id AND t.is_refresh_token = 1
+    ),
+    'enrolled_courses', (SELECT COUNT(*) FROM enrollments e WHERE e.student_id = u.id)
+)";
+
+const PROFILE_ENROLLMENTS: &str = "SELECT id FROM enrollments WHERE student_id = ?1";
+
+const PROFILE_SYNC_ITEMS: &str = "
+    CASE WHEN json_valid(data)
+         THEN COALESCE(json_extract(data, '$.student_id'), json_extract(data, '$.user_id'))
+    END = ?1
+    OR record_id IN (SELECT id FROM enrollments WHERE student_id = ?1)
+    OR record_id IN (
+        SELECT id FROM module_progress
+        WHERE enrollment_id IN (SELECT id FROM enrollments WHERE student_id = ?1)
+    )
+    OR record_id IN (
+        SELECT id FROM content_progress
+        WHERE enrollment_id IN (SELECT id FROM enrollments WHERE student_id = ?1)
+    )
+    OR record_id IN (SELECT id FROM quiz_attempts WHERE student_id = ?1)
+    OR record_id IN (SELECT id FROM bookmarks WHERE student_id = ?1)
+    OR record_id IN (SELECT id FROM notes WHERE student_id = ?1)";
+
+fn ensure_profile_exists(conn: &Connection, user_id: &str) -> Result<(), String> {
+    let exists: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM users WHERE id = ?1)",
+            params![user_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to load profile: {}", e))?;
+
+    if exists {
+        Ok(())
+    } else {
+        Err(format!("Profile {} not found on this device", user_id))
+    }
+}
+
+// Records the profile's work that has not reached the server yet:
+// (unsynced offline batches, pending sync_queue entries)
+fn count_unsynced_profile_data(conn: &Connection, user_id: &str) -> Result<(i64, i64), String> {
+    let unsynced_batches: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM offline_progress_batch
+             WHERE synced = 0
+               AND session_id IN (SELECT id FROM offline_sessions WHERE student_id = ?1)",
+            params![user_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to count unsynced batches: {}", e))?;
+
+    let pending_sync_items: i64 = conn
+        .query_row(
+            &format!("SELECT COUNT(*) FROM sync_queue WHERE {}", PROFILE_SYNC_ITEMS),
+            params![user_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to count pending sync items: {}", e))?;
+
+    Ok((unsynced_batches, pending_sync_items))
+}
+
+// Another learner's profile only opens with their password; the active
+// learner already proved who they are
+fn ensure_profile_unlocked(
+    conn: &Connection,
+    user_id: &str,
+    password: Option<&str>,
+) -> Result<(), String> {
+    if access::current_student_id(conn).as_deref() == Some(user_id) {
+        return Ok(());
+    }
+
+    match password {
+        Some(password) => auth::verify_profile_credential(conn, user_id, password),
+        None => Err(format!("Password required to use profile {}", user_id)),
+    }
+}
+
+// ============================================================================
+// PROFILE COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub fn list_profiles(db_path: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let profiles_json: String = conn
+        .query_row(
+            &format!(
+                "SELECT COALESCE(json_group_array(json(profile)), '[]') FROM (
+                    SELECT {} AS profile FROM users u
+                    ORDER BY u.last_signed_in_at IS NULL, u.last_signed_in_at DESC, u.full_name
+                 )",
+                PROFILE_JSON
+            ),
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to list profiles: {}", e))?;
+
+    Ok(profiles_json)
+}
+
+#[tauri::command]
+pub fn switch_profile(db_path: String, user_id: String, password: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    ensure_profile_exists(&conn, &user_id)?;
+    ensure_profile_unlocked(&conn, &user_id, Some(&password))?;
+
+    // Time the previous learner left running is not credited to the next one
+    if let Some(previous) = access::current_student_id(&conn) {
+        if previous != user_id {
+            activity::close_learner_sessions(&conn, &previous)?;
+        }
+    }
+
+    access::set_active_user(&conn, Some(&user_id))?;
+
+    conn.execute(
+        "UPDATE users SET last_signed_in_at = datetime('now') WHERE id = ?1",
+        params![user_id],
+    )
+    .map_err(|e| format!("Failed to update profile: {}", e))?;
+
+    println!("👤 Switched to profile {}", user_id);
+
+    conn.query_row(
+        &format!("SELECT {} FROM users u WHERE u.id = ?1", PROFILE_JSON),
+        params![user_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Failed to load profile: {}", e))
+}
+
+#[tauri::command]
+pub fn remove_profile(
+    db_path: String,
+    user_id: String,
+    password: Option<String>,
+    discard_unsynced: bool,
+) -> Result<String, String> {
+    let mut conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    ensure_profile_exists(&conn, &user_id)?;
+    ensure_profile_unlocked(&conn, &user_id, password.as_deref())?;
+
+    let (unsynced_batches, pending_sync_items) = count_unsynced_profile_data(&conn, &user_id)?;
+    let has_unsynced = unsynced_batches > 0 || pending_sync_items > 0;
+
+    // ✅ Never throw away progress the server has not seen without being told to
+    if has_unsynced && !discard_unsynced {
+        return Err(format!(
+            "Profile has unsynced progress ({} offline batches, {} queued changes). \
+             Sync first or discard it when removing the profile.",
+            unsynced_batches, pending_sync_items
+        ));
+    }
+
+    let was_active = access::current_student_id(&conn).as_deref() == Some(user_id.as_str());
+
+    let certificate_files: Vec<String> = conn
+        .prepare("SELECT file_path FROM provisional_certificates WHERE user_id = ?1 AND file_path IS NOT NULL")
+        .and_then(|mut stmt| {
+            stmt.query_map(params![user_id], |row| row.get(0))?
+                .collect()
+        })
+        .map_err(|e| format!("Failed to load certificates: {}", e))?;
+
+    let session_ids: Vec<String> = conn
+        .prepare("SELECT id FROM offline_sessions WHERE student_id = ?1")
+        .and_then(|mut stmt| {
+            stmt.query_map(params![user_id], |row| row.get(0))?
+                .collect()
+        })
+        .map_err(|e| format!("Failed to load offline sessions: {}", e))?;
+
+    // Every table is cleared explicitly; cascades from users would skip the
+    // tables that carry no foreign key
+    let (locked_courses, progress_rows, session_rows) = without_foreign_keys(&mut conn, |conn| {
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+        let delete = |label: &str, sql: &str| -> Result<usize, String> {
+            tx.execute(sql, params![user_id])
+                .map_err(|e| format!("Failed to delete {}: {}", label, e))
+        };
+
+        // Downloaded media stays on the device for the other profiles
+        for session_id in &session_ids {
+            media_crypto::revoke_session_key(&tx, session_id)?;
+        }
+
+        // Files no other profile shares a key for have to be downloaded again
+        let locked_courses: Vec<String> = tx
+            .prepare(
+                "SELECT DISTINCT course_id FROM media_cache
+                 WHERE encryption_session_id IN (SELECT id FROM offline_sessions WHERE student_id = ?1)",
+            )
+            .and_then(|mut stmt| {
+                stmt.query_map(params![user_id], |row| row.get(0))?
+                    .collect()
+            })
+            .map_err(|e| format!("Failed to check downloaded media: {}", e))?;
+
+        let mut progress_rows = 0;
+        if has_unsynced {
+            progress_rows += delete(
+                "queued changes",
+                &format!("DELETE FROM sync_queue WHERE {}", PROFILE_SYNC_ITEMS),
+            )?;
+        }
+        progress_rows += delete(
+            "quiz answers",
+            "DELETE FROM quiz_answers
+             WHERE attempt_id IN (SELECT id FROM quiz_attempts WHERE student_id = ?1)",
+        )?;
+        progress_rows += delete(
+            "attempt question order",
+            "DELETE FROM attempt_question_order
+             WHERE attempt_id IN (SELECT id FROM quiz_attempts WHERE student_id = ?1)",
+        )?;
+        progress_rows += delete("quiz attempts", "DELETE FROM quiz_attempts WHERE student_id = ?1")?;
+        for (label, table) in [
+            ("content progress", "content_progress"),
+            ("module progress", "module_progress"),
+            ("resume positions", "resume_positions"),
+            ("learning sessions", "learning_sessions"),
+        ] {
+            progress_rows += delete(
+                label,
+                &format!("DELETE FROM {} WHERE enrollment_id IN ({})", table, PROFILE_ENROLLMENTS),
+            )?;
+        }
+        progress_rows += delete("enrollments", "DELETE FROM enrollments WHERE student_id = ?1")?;
+        progress_rows += delete("review cards", "DELETE FROM review_cards WHERE student_id = ?1")?;
+        progress_rows += delete("activity log", "DELETE FROM activity_log WHERE student_id = ?1")?;
+        progress_rows += delete("weekly goal", "DELETE FROM weekly_goals WHERE student_id = ?1")?;
+        progress_rows += delete("bookmarks", "DELETE FROM bookmarks WHERE student_id = ?1")?;
+        progress_rows += delete("notes", "DELETE FROM notes WHERE student_id = ?1")?;
+        progress_rows += delete(
+            "certificates",
+            "DELETE FROM provisional_certificates WHERE user_id = ?1",
+        )?;
+
+        delete(
+            "progress batches",
+            "DELETE FROM offline_progress_batch
+             WHERE session_id IN (SELECT id FROM offline_sessions WHERE student_id = ?1)",
+        )?;
+        let session_rows = delete(
+            "offline sessions",
+            "DELETE FROM offline_sessions WHERE student_id = ?1",
+        )?;
+        delete("tokens", "DELETE FROM auth_tokens WHERE user_id = ?1")?;
+        delete("profile", "DELETE FROM users WHERE id = ?1")?;
+
+        if was_active {
+            access::set_active_user(&tx, None)?;
+        }
+
+        tx.commit()
+            .map_err(|e| format!("Failed to commit profile removal: {}", e))?;
+
+        Ok((locked_courses, progress_rows, session_rows))
+    })?;
+
+    let mut warnings: Vec<String> = Vec::new();
+    if has_unsynced {
+        warnings.push(format!(
+            "Discarded {} unsynced offline batches and {} queued changes",
+            unsynced_batches, pending_sync_items
+        ));
+    }
+    for course_id in &locked_courses {
+        warnings.push(format!(
+            "Downloaded media for course {} was only readable by this profile; download it again",
+            course_id
+        ));
+    }
+    for path in &certificate_files {
+        if let Err(e) = fs::remove_file(path) {
+            warnings.push(format!("Could not delete certificate {}: {}", path, e));
+        }
+    }
+
+    println!("🗑️ Removed profile {} ({} progress rows)", user_id, progress_rows);
+
+    let report = serde_json::json!({
+        "user_id": user_id,
+        "was_active_profile": was_active,
+        "progress_rows_deleted": progress_rows,
+        "offline_sessions_deleted": session_rows,
+        "certificate_files": certificate_files.len(),
+        "unsynced_batches": unsynced_batches,
+        "pending_sync_items": pending_sync_items,
+        "locked_courses": locked_courses,
+        "warnings": warnings
+    });
+
+    Ok(report.to_string())
+}
@@ -1,5 +1,5 @@
-use crate::commands::get_connection;
-use rusqlite::params;
+use crate::commands::{access, activity, assessment, certificates, get_connection, sync};
+use rusqlite::{params, Connection, OptionalExtension};
 use serde_json::Value as JsonValue;
 
 // ============================================================================
@@ -18,6 +18,8 @@ pub fn save_module_progress(db_path: String, progress_data: String) -> Result<St
     let module_id = progress["module_id"].as_str()
         .ok_or_else(|| "Missing module_id".to_string())?;
 
+    access::ensure_own_enrollment(&conn, enrollment_id)?;
+
     // ✅ Save/update module progress
     conn.execute(
         "INSERT OR REPLACE INTO module_progress
@@ -60,6 +62,8 @@ pub fn get_enrollment_progress(db_path: String, enrollment_id: String) -> Result
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    access::ensure_own_enrollment(&conn, &enrollment_id)?;
+
     let mut stmt = conn
         .prepare(
             "SELECT json_object(
@@ -109,6 +113,16 @@ pub fn update_module_status(
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    let enrollment_id: String = conn
+        .query_row(
+            "SELECT enrollment_id FROM module_progress WHERE id = ?1",
+            params![module_progress_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Module progress not found: {}", e))?;
+
+    access::ensure_own_enrollment(&conn, &enrollment_id)?;
+
     let now = chrono::Utc::now().to_rfc3339();
 
     match status.as_str() {
@@ -127,6 +141,8 @@ pub fn update_module_status(
                 params![status, now, now, module_progress_id],
             )
             .map_err(|e| format!("Failed to update status: {}", e))?;
+
+            evaluate_completion(&conn, &enrollment_id)?;
         }
         _ => {
             conn.execute(
@@ -149,6 +165,8 @@ pub fn get_course_progress_summary(
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    access::ensure_own_enrollment(&conn, &enrollment_id)?;
+
     // ✅ FIXED: Get the course_id from enrollment
     let course_id: String = conn
         .query_row(
@@ -188,29 +206,12 @@ pub fn get_course_progress_summary(
 
     let not_started_modules = total_modules - completed_modules - in_progress_modules;
 
-    // ✅ FIXED: Get last_accessed_at from most recent module_progress update
-    let last_accessed_at: Option<String> = conn
-        .query_row(
-            "SELECT updated_at FROM module_progress
-             WHERE enrollment_id = ?1
-             ORDER BY updated_at DESC
-             LIMIT 1",
-            params![enrollment_id],
-            |row| row.get(0),
-        )
-        .ok();
-
-    // ✅ FIXED: Get last_accessed_module_id
-    let last_accessed_module_id: Option<String> = conn
-        .query_row(
-            "SELECT module_id FROM module_progress
-             WHERE enrollment_id = ?1
-             ORDER BY updated_at DESC
-             LIMIT 1",
-            params![enrollment_id],
-            |row| row.get(0),
-        )
-        .ok();
+    // ✅ FIXED: last access comes from the most recent module_progress update
+    let (last_accessed_module_id, last_accessed_at) =
+        match last_accessed_module(&conn, &enrollment_id) {
+            Some((module_id, updated_at)) => (Some(module_id), Some(updated_at)),
+            None => (None, None),
+        };
 
     // Calculate completion percentage
     let completion_percentage = if total_modules > 0 {
@@ -233,6 +234,19 @@ pub fn get_course_progress_summary(
     Ok(summary.to_string())
 }
 
+// (module_id, updated_at) of the module the learner touched last
+fn last_accessed_module(conn: &Connection, enrollment_id: &str) -> Option<(String, String)> {
+    conn.query_row(
+        "SELECT module_id, updated_at FROM module_progress
+         WHERE enrollment_id = ?1
+         ORDER BY updated_at DESC
+         LIMIT 1",
+        params![enrollment_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .ok()
+}
+
 // ============================================================================
 // CONTENT PROGRESS COMMANDS (NEW - ADDED)
 // ============================================================================
@@ -245,15 +259,29 @@ pub fn save_content_progress(db_path: String, progress_data: String) -> Result<S
     let progress: JsonValue = serde_json::from_str(&progress_data)
         .map_err(|e| format!("Invalid JSON: {}", e))?;
 
+    let enrollment_id = progress["enrollment_id"].as_str().ok_or("Missing enrollment_id")?;
+    access::ensure_own_enrollment(&conn, enrollment_id)?;
+    let is_completed = progress["is_completed"].as_bool().unwrap_or(false);
+
+    let was_completed: bool = conn
+        .query_row(
+            "SELECT is_completed FROM content_progress WHERE enrollment_id = ?1 AND content_id = ?2",
+            params![enrollment_id, progress["content_id"].as_str()],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to load content progress: {}", e))?
+        .unwrap_or(false);
+
     conn.execute(
         "INSERT OR REPLACE INTO content_progress
          (id, enrollment_id, content_id, is_completed, viewed_at, completed_at, created_at, updated_at, last_synced_at)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, datetime('now'))",
         params![
             progress["id"].as_str(),
-            progress["enrollment_id"].as_str(),
+            enrollment_id,
             progress["content_id"].as_str(),
-            progress["is_completed"].as_bool().unwrap_or(false),
+            is_completed,
             progress["viewed_at"].as_str(),
             progress["completed_at"].as_str(),
             progress["created_at"].as_str(),
@@ -262,6 +290,12 @@ pub fn save_content_progress(db_path: String, progress_data: String) -> Result<S
     )
     .map_err(|e| format!("Failed to save content progress: {}", e))?;
 
+    // Progress from sync skips the counters mark_content_as_completed keeps.
+    // Only a change in completion moves them; recompute_progress rebuilds the rest.
+    if is_completed != was_completed {
+        recompute_enrollment(&conn, enrollment_id, RecomputeScope::Full)?;
+    }
+
     Ok("Content progress saved successfully".to_string())
 }
 
@@ -270,6 +304,8 @@ pub fn get_content_progress(db_path: String, enrollment_id: String) -> Result<St
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    access::ensure_own_enrollment(&conn, &enrollment_id)?;
+
     let mut stmt = conn
         .prepare(
             "SELECT json_object(
@@ -306,6 +342,8 @@ pub fn get_content_progress_by_content_id(
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    access::ensure_own_enrollment(&conn, &enrollment_id)?;
+
     let progress_json: String = conn
         .query_row(
             "SELECT json_object(
@@ -362,19 +400,9 @@ pub fn mark_content_as_viewed(
         .map_err(|e| format!("Module not found: {}", e))?;
     println!("📚 Found course_id: {}", course_id);
 
-    // ✅ STEP 3: Get current user's enrollment for this course
+    // ✅ STEP 3: Get the signed-in learner's enrollment for this course
     println!("👤 STEP 3: Getting enrollment_id...");
-    let enrollment_id: String = conn
-        .query_row(
-            "SELECT e.id FROM enrollments e
-             JOIN users u ON e.student_id = u.id
-             WHERE e.course_id = ?1
-             ORDER BY e.created_at DESC
-             LIMIT 1",
-            params![course_id],
-            |row| row.get(0),
-        )
-        .map_err(|e| format!("Enrollment not found: {}", e))?;
+    let enrollment_id = access::require_enrollment_id(&conn, &course_id)?;
     println!("👤 Found enrollment_id: {}", enrollment_id);
 
     let now = chrono::Utc::now().to_rfc3339();
@@ -460,6 +488,8 @@ pub fn mark_content_as_viewed(
     .map_err(|e| format!("Failed to update enrollment timestamp: {}", e))?;
     println!("📅 Enrollment timestamp updated");
 
+    activity::log_enrollment_activity(&conn, &enrollment_id, "content_viewed", &content_id, &now)?;
+
     println!("✅ ========================================");
     println!("✅ mark_content_as_viewed COMPLETE");
     println!("✅ ========================================");
@@ -502,19 +532,9 @@ pub fn mark_content_as_completed(
         .map_err(|e| format!("Module not found: {}", e))?;
     println!("📚 Found course_id: {}", course_id);
 
-    // ✅ STEP 3: Get current user's enrollment for this course
+    // ✅ STEP 3: Get the signed-in learner's enrollment for this course
     println!("👤 STEP 3: Getting enrollment_id...");
-    let enrollment_id: String = conn
-        .query_row(
-            "SELECT e.id FROM enrollments e
-             JOIN users u ON e.student_id = u.id
-             WHERE e.course_id = ?1
-             ORDER BY e.created_at DESC
-             LIMIT 1",
-            params![course_id],
-            |row| row.get(0),
-        )
-        .map_err(|e| format!("Enrollment not found: {}", e))?;
+    let enrollment_id = access::require_enrollment_id(&conn, &course_id)?;
     println!("👤 Found enrollment_id: {}", enrollment_id);
 
     let now = chrono::Utc::now().to_rfc3339();
@@ -532,7 +552,7 @@ pub fn mark_content_as_completed(
         )
         .ok();
 
-    if let Some((is_completed, completed_at)) = existing {
+    if let Some((is_completed, completed_at)) = &existing {
         println!("📝 Existing progress found:");
         println!("   - is_completed: {}", is_completed);
         println!("   - completed_at: {:?}", completed_at);
@@ -572,6 +592,17 @@ pub fn mark_content_as_completed(
     println!("   - is_completed: {}", verification.0);
     println!("   - completed_at: {}", verification.1);
 
+    // Only the first completion counts as activity
+    if !existing.as_ref().map_or(false, |(is_completed, _)| *is_completed) {
+        activity::log_enrollment_activity(
+            &conn,
+            &enrollment_id,
+            "content_completed",
+            &content_id,
+            &verification.1,
+        )?;
+    }
+
     // ✅ STEP 6: Count total content blocks in this module
     println!("📊 STEP 6: Counting total content blocks...");
     let total_content: i64 = conn
@@ -692,6 +723,12 @@ pub fn mark_content_as_completed(
     .map_err(|e| format!("Failed to update enrollment timestamp: {}", e))?;
     println!("📅 Enrollment timestamp updated");
 
+    // ✅ STEP 12: Completing the last module may complete the course
+    if should_auto_complete {
+        let completion = evaluate_completion(&conn, &enrollment_id)?;
+        println!("🏁 Course completed: {}", completion.newly_completed);
+    }
+
     println!("✅ ========================================");
     println!("✅ mark_content_as_completed COMPLETE");
     println!("✅ ========================================");
@@ -748,8 +785,7 @@ fn check_module_auto_completion(
             "SELECT EXISTS(
                 SELECT 1 FROM quiz_attempts qa
                 JOIN quizzes q ON qa.quiz_id = q.id
-                JOIN users u ON qa.student_id = u.id
-                JOIN enrollments e ON u.id = e.student_id
+                JOIN enrollments e ON qa.student_id = e.student_id
                 WHERE q.module_id = ?1
                   AND e.id = ?2
                   AND qa.status = 'completed'
@@ -764,38 +800,838 @@ fn check_module_auto_completion(
 }
 
 // ============================================================================
-// QUIZ ATTEMPT COMMANDS
+// COURSE COMPLETION
 // ============================================================================
 
+pub struct CourseCompletion {
+    pub enrollment_id: String,
+    pub status: String,
+    pub completed_at: Option<String>,
+    pub total_modules: i64,
+    pub completed_modules: i64,
+    pub final_exam_id: Option<String>,
+    pub final_exam_passed: bool,
+    pub requirements_met: bool,
+    // True only for the evaluation that moved the enrollment to completed
+    pub newly_completed: bool,
+}
+
+impl CourseCompletion {
+    pub fn to_json(&self) -> JsonValue {
+        serde_json::json!({
+            "enrollment_id": self.enrollment_id,
+            "status": self.status,
+            "completed_at": self.completed_at,
+            "total_modules": self.total_modules,
+            "completed_modules": self.completed_modules,
+            "final_exam_id": self.final_exam_id,
+            "final_exam_passed": self.final_exam_passed,
+            "requirements_met": self.requirements_met,
+            "newly_completed": self.newly_completed
+        })
+    }
+}
+
+// A course is complete once every module is completed and the final exam,
+// if the course has one, has a passed attempt. Only active enrollments are
+// moved to completed; dropped ones are left alone.
+pub fn evaluate_completion(conn: &Connection, enrollment_id: &str) -> Result<CourseCompletion, String> {
+    let (student_id, course_id, status, completed_at): (String, String, Option<String>, Option<String>) = conn
+        .query_row(
+            "SELECT student_id, course_id, status, completed_at FROM enrollments WHERE id = ?1",
+            params![enrollment_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|e| format!("Enrollment not found: {}", e))?;
+
+    let (total_modules, completed_modules): (i64, i64) = conn
+        .query_row(
+            "SELECT COUNT(*),
+                    COUNT(CASE WHEN EXISTS(
+                        SELECT 1 FROM module_progress mp
+                        WHERE mp.module_id = m.id AND mp.enrollment_id = ?2
+                          AND mp.status = 'completed'
+                    ) THEN 1 END)
+             FROM modules m
+             WHERE m.course_id = ?1",
+            params![course_id, enrollment_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Failed to count modules: {}", e))?;
+
+    let final_exam_id: Option<String> = conn
+        .query_row(
+            "SELECT id FROM quizzes WHERE course_id = ?1 AND quiz_type = 'final_exam' LIMIT 1",
+            params![course_id],
+            |row| row.get(0),
+        )
+        .ok();
+
+    let final_exam_passed = match &final_exam_id {
+        Some(quiz_id) => conn
+            .query_row(
+                "SELECT EXISTS(
+                    SELECT 1 FROM quiz_attempts
+                    WHERE quiz_id = ?1 AND student_id = ?2
+                      AND status = 'completed' AND passed = 1
+                 )",
+                params![quiz_id, student_id],
+                |row| row.get(0),
+            )
+            .unwrap_or(false),
+        None => false,
+    };
+
+    let requirements_met = (total_modules > 0 || final_exam_id.is_some())
+        && completed_modules == total_modules
+        && (final_exam_id.is_none() || final_exam_passed);
+
+    let mut completion = CourseCompletion {
+        enrollment_id: enrollment_id.to_string(),
+        status: status.unwrap_or_else(|| "active".to_string()),
+        completed_at,
+        total_modules,
+        completed_modules,
+        final_exam_id,
+        final_exam_passed,
+        requirements_met,
+        newly_completed: false,
+    };
+
+    if !requirements_met || completion.status != "active" {
+        return Ok(completion);
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        "UPDATE enrollments
+         SET status = 'completed', completed_at = ?1, updated_at = ?1
+         WHERE id = ?2",
+        params![now, enrollment_id],
+    )
+    .map_err(|e| format!("Failed to complete enrollment: {}", e))?;
+
+    let change = serde_json::json!({
+        "id": enrollment_id,
+        "student_id": student_id,
+        "course_id": course_id,
+        "status": "completed",
+        "completed_at": now,
+        "updated_at": now
+    });
+    sync::queue_change(conn, "update", "enrollments", enrollment_id, &change.to_string())?;
+
+    println!("🎓 Enrollment {} completed course {}", enrollment_id, course_id);
+
+    // ✅ Learners finishing offline get a provisional certificate right away
+    certificates::issue_provisional_certificate(conn, enrollment_id)?;
+
+    completion.status = "completed".to_string();
+    completion.completed_at = Some(now);
+    completion.newly_completed = true;
+
+    Ok(completion)
+}
+
+// The enrollment an attempt counts towards
+fn attempt_enrollment_id(conn: &Connection, attempt_id: &str) -> Option<String> {
+    conn.query_row(
+        "SELECT e.id FROM quiz_attempts qa
+         JOIN quizzes q ON qa.quiz_id = q.id
+         LEFT JOIN modules m ON q.module_id = m.id
+         JOIN enrollments e
+           ON e.student_id = qa.student_id
+          AND e.course_id = COALESCE(q.course_id, m.course_id)
+         WHERE qa.id = ?1",
+        params![attempt_id],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+// Re-evaluates the enrollment a passed attempt counts towards. A passed
+// module quiz may complete its module, so the module statuses are rebuilt too.
+pub fn evaluate_completion_for_attempt(conn: &Connection, attempt_id: &str) -> Result<(), String> {
+    if let Some(enrollment_id) = attempt_enrollment_id(conn, attempt_id) {
+        recompute_enrollment(conn, &enrollment_id, RecomputeScope::Full)?;
+    }
+
+    Ok(())
+}
+
 #[tauri::command]
-pub fn save_quiz_attempt(db_path: String, attempt_data: String) -> Result<String, String> {
+pub fn evaluate_course_completion(db_path: String, enrollment_id: String) -> Result<String, String> {
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
-    let attempt: JsonValue = serde_json::from_str(&attempt_data)
+    access::ensure_own_enrollment(&conn, &enrollment_id)?;
+
+    let completion = evaluate_completion(&conn, &enrollment_id)?;
+
+    Ok(completion.to_json().to_string())
+}
+
+// ============================================================================
+// PROGRESS RECOMPUTE
+// ============================================================================
+// module_progress keeps counters that mark_content_as_completed maintains as
+// the learner works. Imported content and progress arriving from sync bypass
+// it, so the counters and module statuses are rebuilt from content_progress
+// and quiz_attempts here. A completed module is never reopened.
+
+// Completing a module or the course cannot be undone, so a recompute only
+// does it once all of a course's content is in place
+#[derive(Clone, Copy, PartialEq)]
+pub enum RecomputeScope {
+    // Counters and in-progress statuses, while an import is still saving content
+    CountersOnly,
+    // Also completes modules and evaluates course completion
+    Full,
+}
+
+pub struct ProgressRecompute {
+    pub enrollment_id: String,
+    pub modules_checked: usize,
+    pub changes: Vec<JsonValue>,
+    pub course_completed: bool,
+}
+
+impl ProgressRecompute {
+    pub fn to_json(&self) -> JsonValue {
+        serde_json::json!({
+            "enrollment_id": self.enrollment_id,
+            "modules_checked": self.modules_checked,
+            "modules_changed": self.changes.len(),
+            "changes": self.changes,
+            "course_completed": self.course_completed
+        })
+    }
+}
+
+// (status, completed_content_count, total_content_count, content_completion_percentage)
+type ModuleCounters = (String, i64, i64, f64);
+
+fn counters_json(counters: &ModuleCounters) -> JsonValue {
+    serde_json::json!({
+        "status": counters.0,
+        "completed_content_count": counters.1,
+        "total_content_count": counters.2,
+        "content_completion_percentage": counters.3
+    })
+}
+
+pub fn recompute_enrollment(
+    conn: &Connection,
+    enrollment_id: &str,
+    scope: RecomputeScope,
+) -> Result<ProgressRecompute, String> {
+    let (student_id, course_id): (String, String) = conn
+        .query_row(
+            "SELECT student_id, course_id FROM enrollments WHERE id = ?1",
+            params![enrollment_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Enrollment not found: {}", e))?;
+
+    type ModuleRow = (String, i64, i64, bool, Option<String>, Option<ModuleCounters>);
+
+    let modules: Vec<ModuleRow> = conn
+        .prepare(
+            "SELECT m.id,
+                    (SELECT COUNT(*) FROM content_blocks cb WHERE cb.module_id = m.id),
+                    (SELECT COUNT(*) FROM content_progress cp
+                     JOIN content_blocks cb ON cp.content_id = cb.id
+                     WHERE cp.enrollment_id = ?2 AND cb.module_id = m.id AND cp.is_completed = 1),
+                    EXISTS(
+                        SELECT 1 FROM content_progress cp
+                        JOIN content_blocks cb ON cp.content_id = cb.id
+                        WHERE cp.enrollment_id = ?2 AND cb.module_id = m.id
+                    ) OR EXISTS(
+                        SELECT 1 FROM quiz_attempts qa
+                        JOIN quizzes q ON qa.quiz_id = q.id
+                        WHERE q.module_id = m.id AND qa.student_id = ?3
+                    ),
+                    mp.id, mp.status, mp.completed_content_count, mp.total_content_count,
+                    mp.content_completion_percentage
+             FROM modules m
+             LEFT JOIN module_progress mp ON mp.module_id = m.id AND mp.enrollment_id = ?2
+             WHERE m.course_id = ?1
+             ORDER BY m.order_index",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(params![course_id, enrollment_id, student_id], |row| {
+                let progress_id: Option<String> = row.get(4)?;
+                let stored = match progress_id {
+                    Some(_) => Some((
+                        row.get::<_, Option<String>>(5)?.unwrap_or_else(|| "not_started".to_string()),
+                        row.get::<_, Option<i64>>(6)?.unwrap_or(0),
+                        row.get::<_, Option<i64>>(7)?.unwrap_or(0),
+                        row.get::<_, Option<f64>>(8)?.unwrap_or(0.0),
+                    )),
+                    None => None,
+                };
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, progress_id, stored))
+            })?
+            .collect()
+        })
+        .map_err(|e| format!("Failed to load module progress: {}", e))?;
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut changes = Vec::new();
+
+    for (module_id, total_content, completed_content, started, progress_id, stored) in &modules {
+        let percentage = if *total_content > 0 {
+            ((*completed_content as f64 / *total_content as f64) * 100.0).round()
+        } else {
+            0.0
+        };
+
+        let stored_status = stored.as_ref().map(|s| s.0.as_str());
+        let status = if stored_status == Some("completed")
+            || (scope == RecomputeScope::Full
+                && check_module_auto_completion(conn, enrollment_id, module_id)?)
+        {
+            "completed"
+        } else if *started || *completed_content > 0 {
+            "in_progress"
+        } else {
+            stored_status.unwrap_or("not_started")
+        };
+
+        let rebuilt: ModuleCounters = (status.to_string(), *completed_content, *total_content, percentage);
+
+        if let Some(stored) = stored {
+            let unchanged = stored.0 == rebuilt.0
+                && stored.1 == rebuilt.1
+                && stored.2 == rebuilt.2
+                && (stored.3 - rebuilt.3).abs() < 0.01;
+            if unchanged {
+                continue;
+            }
+        } else if status == "not_started" {
+            // Untouched modules get their row when the learner opens them
+            continue;
+        }
+
+        let newly_completed = status == "completed" && stored_status != Some("completed");
+        let started_at = (status != "not_started").then_some(now.as_str());
+        let completed_at = newly_completed.then_some(now.as_str());
+
+        match progress_id {
+            Some(progress_id) => conn.execute(
+                "UPDATE module_progress
+                 SET status = ?1,
+                     completed_content_count = ?2,
+                     total_content_count = ?3,
+                     content_completion_percentage = ?4,
+                     started_at = COALESCE(started_at, ?5),
+                     completed_at = COALESCE(?6, completed_at),
+                     auto_completed = CASE WHEN ?6 IS NOT NULL THEN 1 ELSE auto_completed END,
+                     updated_at = ?7,
+                     last_synced_at = datetime('now')
+                 WHERE id = ?8",
+                params![
+                    status,
+                    completed_content,
+                    total_content,
+                    percentage,
+                    started_at,
+                    completed_at,
+                    now,
+                    progress_id
+                ],
+            ),
+            None => conn.execute(
+                "INSERT INTO module_progress
+                 (id, enrollment_id, module_id, status, started_at, completed_at, auto_completed,
+                  content_completion_percentage, completed_content_count, total_content_count,
+                  created_at, updated_at, last_synced_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?11, datetime('now'))",
+                params![
+                    format!("mp_{}_{}", enrollment_id, module_id),
+                    enrollment_id,
+                    module_id,
+                    status,
+                    started_at,
+                    completed_at,
+                    newly_completed,
+                    percentage,
+                    completed_content,
+                    total_content,
+                    now
+                ],
+            ),
+        }
+        .map_err(|e| format!("Failed to update module progress: {}", e))?;
+
+        changes.push(serde_json::json!({
+            "module_id": module_id,
+            "before": stored.as_ref().map(counters_json),
+            "after": counters_json(&rebuilt)
+        }));
+    }
+
+    // ✅ Rebuilt statuses (or a passed final exam) may complete the course
+    let course_completed = match scope {
+        RecomputeScope::Full => evaluate_completion(conn, enrollment_id)?.newly_completed,
+        RecomputeScope::CountersOnly => false,
+    };
+
+    Ok(ProgressRecompute {
+        enrollment_id: enrollment_id.to_string(),
+        modules_checked: modules.len(),
+        changes,
+        course_completed,
+    })
+}
+
+// Runs as content is saved, for every enrollment in the affected courses.
+// Only the counters are refreshed: a package is saved in several calls, and a
+// half-imported course must not complete anything. The importer calls
+// recompute_progress once the whole package is in. Returns the number of
+// module rows that changed.
+pub fn recompute_after_import(conn: &Connection, module_ids: &[String]) -> Result<usize, String> {
+    let mut enrollment_ids: Vec<String> = Vec::new();
+
+    for module_id in module_ids {
+        let ids: Vec<String> = conn
+            .prepare(
+                "SELECT e.id FROM enrollments e
+                 JOIN modules m ON m.course_id = e.course_id
+                 WHERE m.id = ?1",
+            )
+            .and_then(|mut stmt| {
+                stmt.query_map(params![module_id], |row| row.get(0))?
+                    .collect()
+            })
+            .map_err(|e| format!("Failed to load enrollments: {}", e))?;
+
+        for id in ids {
+            if !enrollment_ids.contains(&id) {
+                enrollment_ids.push(id);
+            }
+        }
+    }
+
+    let mut changed = 0;
+    for enrollment_id in &enrollment_ids {
+        changed += recompute_enrollment(conn, enrollment_id, RecomputeScope::CountersOnly)?
+            .changes
+            .len();
+    }
+
+    if changed > 0 {
+        println!("🔁 Recomputed progress after import: {} module rows changed", changed);
+    }
+
+    Ok(changed)
+}
+
+#[tauri::command]
+pub fn recompute_progress(db_path: String, enrollment_id: Option<String>) -> Result<String, String> {
+    let mut conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    // Without an id, every enrollment of the signed-in learner is rebuilt
+    let enrollment_ids: Vec<String> = match enrollment_id {
+        Some(id) => {
+            access::ensure_own_enrollment(&conn, &id)?;
+            vec![id]
+        }
+        None => {
+            let student_id = access::require_student_id(&conn)?;
+            conn.prepare("SELECT id FROM enrollments WHERE student_id = ?1 ORDER BY enrolled_at")
+                .and_then(|mut stmt| {
+                    stmt.query_map(params![student_id], |row| row.get(0))?
+                        .collect()
+                })
+                .map_err(|e| format!("Failed to load enrollments: {}", e))?
+        }
+    };
+
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let mut results = Vec::new();
+    for enrollment_id in &enrollment_ids {
+        results.push(recompute_enrollment(&tx, enrollment_id, RecomputeScope::Full)?);
+    }
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit progress recompute: {}", e))?;
+
+    let report = serde_json::json!({
+        "enrollments_checked": results.len(),
+        "modules_checked": results.iter().map(|r| r.modules_checked).sum::<usize>(),
+        "modules_changed": results.iter().map(|r| r.changes.len()).sum::<usize>(),
+        "courses_completed": results.iter().filter(|r| r.course_completed).count(),
+        "enrollments": results.iter().map(|r| r.to_json()).collect::<Vec<_>>()
+    });
+
+    Ok(report.to_string())
+}
+
+// ============================================================================
+// RESUME POSITION COMMANDS
+// ============================================================================
+
+const RESUME_POSITION_JSON: &str = "json_object(
+    'enrollment_id', rp.enrollment_id,
+    'content_id', rp.content_id,
+    'position_type', rp.position_type,
+    'media_seconds', rp.media_seconds,
+    'scroll_offset', rp.scroll_offset,
+    'page_number', rp.page_number,
+    'updated_at', rp.updated_at
+)";
+
+#[tauri::command]
+pub fn save_resume_position(
+    db_path: String,
+    enrollment_id: String,
+    content_id: String,
+    position_data: String,
+) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    access::ensure_own_enrollment(&conn, &enrollment_id)?;
+
+    let position: JsonValue = serde_json::from_str(&position_data)
         .map_err(|e| format!("Invalid JSON: {}", e))?;
 
+    let position_type = position["position_type"]
+        .as_str()
+        .ok_or("Missing position_type")?;
+
+    // Each type carries exactly one position value
+    let (media_seconds, scroll_offset, page_number) = match position_type {
+        "media" => (
+            Some(position["media_seconds"].as_f64().ok_or("Missing media_seconds")?.max(0.0)),
+            None,
+            None,
+        ),
+        "scroll" => (
+            None,
+            Some(position["scroll_offset"].as_f64().ok_or("Missing scroll_offset")?.clamp(0.0, 1.0)),
+            None,
+        ),
+        "page" => (
+            None,
+            None,
+            Some(position["page_number"].as_i64().ok_or("Missing page_number")?.max(1)),
+        ),
+        other => {
+            return Err(format!(
+                "Unknown position_type '{}'. Expected media, scroll or page",
+                other
+            ))
+        }
+    };
+
+    let belongs_to_course: bool = conn
+        .query_row(
+            "SELECT EXISTS(
+                SELECT 1 FROM content_blocks cb
+                JOIN modules m ON cb.module_id = m.id
+                JOIN enrollments e ON e.course_id = m.course_id
+                WHERE cb.id = ?1 AND e.id = ?2
+             )",
+            params![content_id, enrollment_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to check content: {}", e))?;
+
+    if !belongs_to_course {
+        return Err(format!(
+            "Content {} is not part of the course of enrollment {}",
+            content_id, enrollment_id
+        ));
+    }
+
     conn.execute(
-        "INSERT OR REPLACE INTO quiz_attempts
-         (id, student_id, quiz_id, attempt_number, status, started_at, completed_at,
-          score, passed, time_remaining_seconds, created_at, updated_at, last_synced_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, datetime('now'))",
+        "INSERT INTO resume_positions
+         (enrollment_id, content_id, position_type, media_seconds, scroll_offset, page_number, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(enrollment_id, content_id) DO UPDATE SET
+            position_type = excluded.position_type,
+            media_seconds = excluded.media_seconds,
+            scroll_offset = excluded.scroll_offset,
+            page_number = excluded.page_number,
+            updated_at = excluded.updated_at",
         params![
-            attempt["id"].as_str(),
-            attempt["student_id"].as_str(),
-            attempt["quiz_id"].as_str(),
-            attempt["attempt_number"].as_i64(),
-            attempt["status"].as_str(),
-            attempt["started_at"].as_str(),
-            attempt["completed_at"].as_str(),
-            attempt["score"].as_f64(),
-            attempt["passed"].as_bool(),
-            attempt["time_remaining_seconds"].as_i64(),
-            attempt["created_at"].as_str(),
-            attempt["updated_at"].as_str(),
+            enrollment_id,
+            content_id,
+            position_type,
+            media_seconds,
+            scroll_offset,
+            page_number,
+            chrono::Utc::now().to_rfc3339()
         ],
     )
-    .map_err(|e| format!("Failed to save quiz attempt: {}", e))?;
+    .map_err(|e| format!("Failed to save resume position: {}", e))?;
+
+    Ok("Resume position saved successfully".to_string())
+}
+
+fn load_resume_position(conn: &Connection, enrollment_id: &str, content_id: &str) -> Option<String> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM resume_positions rp
+             WHERE rp.enrollment_id = ?1 AND rp.content_id = ?2",
+            RESUME_POSITION_JSON
+        ),
+        params![enrollment_id, content_id],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+// Returns null when the block has never been opened
+#[tauri::command]
+pub fn get_resume_position(
+    db_path: String,
+    enrollment_id: String,
+    content_id: String,
+) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    access::ensure_own_enrollment(&conn, &enrollment_id)?;
+
+    Ok(load_resume_position(&conn, &enrollment_id, &content_id)
+        .unwrap_or_else(|| "null".to_string()))
+}
+
+// Picks the block to reopen: the most recently saved position in the last
+// accessed module, else that module's first unfinished block, else the first
+// unfinished block of any later module. Completed blocks are skipped.
+#[tauri::command]
+pub fn get_continue_learning(db_path: String, enrollment_id: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    access::ensure_own_enrollment(&conn, &enrollment_id)?;
+
+    let course_id: String = conn
+        .query_row(
+            "SELECT course_id FROM enrollments WHERE id = ?1",
+            params![enrollment_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Enrollment not found: {}", e))?;
+
+    let last_module = last_accessed_module(&conn, &enrollment_id);
+
+    const UNFINISHED: &str = "NOT EXISTS(
+        SELECT 1 FROM content_progress cp
+        WHERE cp.content_id = cb.id AND cp.enrollment_id = ?1 AND cp.is_completed = 1
+    )";
+
+    let resumed: Option<(String, &str)> = match &last_module {
+        Some((module_id, _)) => conn
+            .query_row(
+                &format!(
+                    "SELECT cb.id FROM resume_positions rp
+                     JOIN content_blocks cb ON rp.content_id = cb.id
+                     WHERE rp.enrollment_id = ?1 AND cb.module_id = ?2 AND {}
+                     ORDER BY rp.updated_at DESC
+                     LIMIT 1",
+                    UNFINISHED
+                ),
+                params![enrollment_id, module_id],
+                |row| row.get(0),
+            )
+            .ok()
+            .map(|id| (id, "resume_position"))
+            .or_else(|| {
+                conn.query_row(
+                    &format!(
+                        "SELECT cb.id FROM content_blocks cb
+                         WHERE cb.module_id = ?2 AND {}
+                         ORDER BY cb.order_index ASC, cb.id ASC
+                         LIMIT 1",
+                        UNFINISHED
+                    ),
+                    params![enrollment_id, module_id],
+                    |row| row.get(0),
+                )
+                .ok()
+                .map(|id| (id, "last_accessed_module"))
+            }),
+        None => None,
+    };
+
+    // Fall back to the first unfinished block at or after the last module
+    let next = resumed.or_else(|| {
+        let after_order: i64 = last_module
+            .as_ref()
+            .and_then(|(module_id, _)| {
+                conn.query_row(
+                    "SELECT order_index FROM modules WHERE id = ?1",
+                    params![module_id],
+                    |row| row.get::<_, Option<i64>>(0),
+                )
+                .ok()
+                .flatten()
+            })
+            .unwrap_or(i64::MIN);
+
+        conn.query_row(
+            &format!(
+                "SELECT cb.id FROM content_blocks cb
+                 JOIN modules m ON cb.module_id = m.id
+                 WHERE m.course_id = ?2 AND COALESCE(m.order_index, 0) >= ?3 AND {}
+                 ORDER BY m.order_index ASC, m.id ASC, cb.order_index ASC, cb.id ASC
+                 LIMIT 1",
+                UNFINISHED
+            ),
+            params![enrollment_id, course_id, after_order],
+            |row| row.get(0),
+        )
+        .ok()
+        .map(|id| (id, if last_module.is_some() { "next_module" } else { "course_start" }))
+    });
+
+    let (content_id, reason) = match next {
+        Some(found) => found,
+        None => {
+            let nothing_left = serde_json::json!({
+                "enrollment_id": enrollment_id,
+                "course_id": course_id,
+                "last_accessed_module_id": last_module.map(|(id, _)| id),
+                "content": null,
+                "resume_position": null,
+                "reason": "all_content_completed"
+            });
+            return Ok(nothing_left.to_string());
+        }
+    };
+
+    let content: JsonValue = conn
+        .query_row(
+            "SELECT json_object(
+                'content_id', cb.id,
+                'content_title', cb.title,
+                'module_id', m.id,
+                'module_title', m.title
+             ) FROM content_blocks cb
+             JOIN modules m ON cb.module_id = m.id
+             WHERE cb.id = ?1",
+            params![content_id],
+            |row| row.get::<_, String>(0),
+        )
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or(JsonValue::Null);
+
+    let resume_position: JsonValue = load_resume_position(&conn, &enrollment_id, &content_id)
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or(JsonValue::Null);
+
+    let continue_learning = serde_json::json!({
+        "enrollment_id": enrollment_id,
+        "course_id": course_id,
+        "last_accessed_module_id": last_module.map(|(id, _)| id),
+        "content": content,
+        "resume_position": resume_position,
+        "reason": reason
+    });
+
+    Ok(continue_learning.to_string())
+}
+
+// ============================================================================
+// QUIZ ATTEMPT COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub fn save_quiz_attempt(db_path: String, attempt_data: String) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let attempt: JsonValue = serde_json::from_str(&attempt_data)
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let student_id = attempt["student_id"].as_str().ok_or("Missing student_id")?;
+    access::ensure_current_student(&conn, student_id)?;
+
+    // ✅ Only assessment::finalize_attempt completes and scores an attempt;
+    // the webview can record one in progress and nothing more
+    match attempt["status"].as_str() {
+        None | Some("in_progress") => {}
+        Some(status) => {
+            return Err(format!(
+                "Cannot save a quiz attempt as '{}'. Submit it to have it graded",
+                status
+            ))
+        }
+    }
+
+    let attempt_id = attempt["id"].as_str().ok_or("Missing id")?;
+    let quiz_id = attempt["quiz_id"].as_str().ok_or("Missing quiz_id")?;
+
+    // ✅ A new attempt counts against the same window start_quiz_attempt
+    // enforces, and is numbered here rather than by the webview
+    let existing_number: Option<i64> = conn
+        .query_row(
+            "SELECT attempt_number FROM quiz_attempts WHERE id = ?1",
+            params![attempt_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to load quiz attempt: {}", e))?;
+
+    let attempt_number = match existing_number {
+        Some(number) => number,
+        None => assessment::get_attempt_window(&conn, quiz_id, student_id)
+            .and_then(|window| {
+                assessment::ensure_can_attempt(&conn, quiz_id, student_id)?;
+                assessment::next_attempt_number(&conn, quiz_id, student_id, window)
+            })
+            .map_err(|e| serde_json::json!(e).to_string())?,
+    };
+
+    let deadline_at = match attempt["started_at"].as_str() {
+        Some(started_at) => assessment::compute_deadline(&conn, quiz_id, started_at).unwrap_or(None),
+        None => None,
+    };
+
+    // ✅ Upsert rather than REPLACE: a REPLACE deletes the row first, which
+    // cascades to the attempt's answers and resets its deadline. Submitted
+    // attempts and other learners' attempts are never touched.
+    let saved = conn
+        .execute(
+            "INSERT INTO quiz_attempts
+             (id, student_id, quiz_id, attempt_number, status, started_at,
+              time_remaining_seconds, created_at, updated_at, deadline_at, last_synced_at)
+             VALUES (?1, ?2, ?3, ?4, 'in_progress', ?5, ?6, ?7, ?8, ?9, datetime('now'))
+             ON CONFLICT(id) DO UPDATE SET
+                time_remaining_seconds = excluded.time_remaining_seconds,
+                updated_at = excluded.updated_at,
+                deadline_at = COALESCE(deadline_at, excluded.deadline_at),
+                last_synced_at = excluded.last_synced_at
+             WHERE quiz_attempts.status = 'in_progress'
+               AND quiz_attempts.student_id = excluded.student_id",
+            params![
+                attempt_id,
+                student_id,
+                quiz_id,
+                attempt_number,
+                attempt["started_at"].as_str(),
+                attempt["time_remaining_seconds"].as_i64(),
+                attempt["created_at"].as_str(),
+                attempt["updated_at"].as_str(),
+                deadline_at,
+            ],
+        )
+        .map_err(|e| format!("Failed to save quiz attempt: {}", e))?;
+
+    if saved == 0 {
+        return Err("Quiz attempt has already been submitted".to_string());
+    }
 
     Ok("Quiz attempt saved successfully".to_string())
 }
@@ -805,6 +1641,10 @@ pub fn get_quiz_attempts(db_path: String, quiz_id: String, student_id: String) -
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    access::ensure_current_student(&conn, &student_id)?;
+
+    assessment::finalize_overdue_attempts(&conn)?;
+
     let mut stmt = conn
         .prepare(
             "SELECT json_object(
@@ -817,7 +1657,12 @@ pub fn get_quiz_attempts(db_path: String, quiz_id: String, student_id: String) -
                 'completed_at', completed_at,
                 'score', score,
                 'passed', passed,
-                'time_remaining_seconds', time_remaining_seconds,
+                'deadline_at', deadline_at,
+                'time_remaining_seconds', CASE
+                    WHEN deadline_at IS NOT NULL AND status = 'in_progress'
+                    THEN MAX(0, CAST((julianday(deadline_at) - julianday('now')) * 86400 AS INTEGER))
+                    ELSE time_remaining_seconds
+                END,
                 'created_at', created_at,
                 'updated_at', updated_at
              ) FROM quiz_attempts
@@ -841,6 +1686,10 @@ pub fn get_quiz_attempt_by_id(db_path: String, attempt_id: String) -> Result<Str
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    access::ensure_own_attempt(&conn, &attempt_id)?;
+
+    assessment::finalize_overdue_attempts(&conn)?;
+
     let attempt_json: String = conn
         .query_row(
             "SELECT json_object(
@@ -853,7 +1702,12 @@ pub fn get_quiz_attempt_by_id(db_path: String, attempt_id: String) -> Result<Str
                 'completed_at', completed_at,
                 'score', score,
                 'passed', passed,
-                'time_remaining_seconds', time_remaining_seconds,
+                'deadline_at', deadline_at,
+                'time_remaining_seconds', CASE
+                    WHEN deadline_at IS NOT NULL AND status = 'in_progress'
+                    THEN MAX(0, CAST((julianday(deadline_at) - julianday('now')) * 86400 AS INTEGER))
+                    ELSE time_remaining_seconds
+                END,
                 'created_at', created_at,
                 'updated_at', updated_at
              ) FROM quiz_attempts WHERE id = ?1",
@@ -876,24 +1730,33 @@ pub fn update_quiz_attempt_status(
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    access::ensure_own_attempt(&conn, &attempt_id)?;
+
     let now = chrono::Utc::now().to_rfc3339();
 
     if status == "completed" {
-        conn.execute(
-            "UPDATE quiz_attempts
-             SET status = ?1, completed_at = ?2, score = ?3, passed = ?4, updated_at = ?5, last_synced_at = datetime('now')
-             WHERE id = ?6",
-            params![status, now, score, passed, now, attempt_id],
-        )
-        .map_err(|e| format!("Failed to update attempt: {}", e))?;
-    } else {
+        // ✅ The score is always recomputed from the answer key
+        let grade = assessment::finalize_attempt(&conn, &attempt_id)?;
+
+        if score.map_or(false, |s| (s - grade.percentage).abs() > 0.01)
+            || passed.map_or(false, |p| p != grade.passed)
+        {
+            println!(
+                "⚠️ Ignoring client score for attempt {} ({:?}/{:?}), graded {}%",
+                attempt_id, score, passed, grade.percentage
+            );
+        }
+    } else if status == "abandoned" {
+        // Only an attempt still in progress can be abandoned
         conn.execute(
             "UPDATE quiz_attempts
              SET status = ?1, updated_at = ?2, last_synced_at = datetime('now')
-             WHERE id = ?3",
+             WHERE id = ?3 AND status = 'in_progress'",
             params![status, now, attempt_id],
         )
         .map_err(|e| format!("Failed to update attempt: {}", e))?;
+    } else {
+        return Err(format!("Cannot set a quiz attempt to '{}'", status));
     }
 
     Ok("Quiz attempt updated successfully".to_string())
@@ -911,29 +1774,49 @@ pub fn save_quiz_answer(db_path: String, answer_data: String) -> Result<String,
     let answer: JsonValue = serde_json::from_str(&answer_data)
         .map_err(|e| format!("Invalid JSON: {}", e))?;
 
-    // ✅ Convert is_correct to explicit integer for SQLite
-    let is_correct_value = match answer["is_correct"].as_i64() {
-        Some(v) => v,
-        None => match answer["is_correct"].as_bool() {
-            Some(true) => 1,
-            Some(false) => 0,
-            None => 0
-        }
-    };
+    let attempt_id = answer["attempt_id"].as_str().ok_or("Missing attempt_id")?;
+    let question_id = answer["question_id"].as_str().ok_or("Missing question_id")?;
+    let input = assessment::AnswerInput::from_json(&answer);
+
+    access::ensure_own_attempt(&conn, attempt_id)?;
+
+    let status: Option<String> = conn
+        .query_row(
+            "SELECT status FROM quiz_attempts WHERE id = ?1",
+            params![attempt_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Attempt not found: {}", e))?;
+
+    if status.as_deref() != Some("in_progress") {
+        return Err(format!("Attempt {} is no longer accepting answers", attempt_id));
+    }
+
+    if assessment::is_overdue(&conn, attempt_id)? {
+        assessment::finalize_attempt(&conn, attempt_id)?;
+        return Err(assessment::attempt_expired_error(attempt_id));
+    }
+
+    // ✅ Graded here - is_correct / points_earned sent by the webview are ignored
+    let graded = assessment::grade_answer(&conn, attempt_id, question_id, &input)?;
 
     conn.execute(
         "INSERT OR REPLACE INTO quiz_answers
-         (id, attempt_id, question_id, selected_option_id, is_correct, points_earned, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+         (id, attempt_id, question_id, selected_option_id, selected_option_ids, text_answer,
+          is_correct, points_earned, created_at, updated_at, answered_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
         params![
             answer["id"].as_str(),
-            answer["attempt_id"].as_str(),
-            answer["question_id"].as_str(),
-            answer["selected_option_id"].as_str(),
-            is_correct_value,  // ✅ Explicit integer
-            answer["points_earned"].as_f64(),
+            attempt_id,
+            question_id,
+            input.selected_option_id,
+            input.selected_option_ids_json(),
+            input.text_answer,
+            graded.is_correct,
+            graded.points_earned,
             answer["created_at"].as_str(),
             answer["updated_at"].as_str(),
+            chrono::Utc::now().to_rfc3339(),
         ],
     )
     .map_err(|e| format!("Failed to save quiz answer: {}", e))?;
@@ -941,11 +1824,14 @@ pub fn save_quiz_answer(db_path: String, answer_data: String) -> Result<String,
     Ok("Quiz answer saved successfully".to_string())
 }
 
+// Grading stays hidden until the attempt is finished
 #[tauri::command]
 pub fn get_attempt_answers(db_path: String, attempt_id: String) -> Result<String, String> {
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    access::ensure_own_attempt(&conn, &attempt_id)?;
+
     let mut stmt = conn
         .prepare(
             "SELECT json_object(
@@ -953,12 +1839,15 @@ pub fn get_attempt_answers(db_path: String, attempt_id: String) -> Result<String
                 'attempt_id', qa.attempt_id,
                 'question_id', qa.question_id,
                 'selected_option_id', qa.selected_option_id,
-                'is_correct', qa.is_correct,
-                'points_earned', qa.points_earned,
+                'selected_option_ids', json(qa.selected_option_ids),
+                'text_answer', qa.text_answer,
+                'is_correct', CASE WHEN a.status = 'in_progress' THEN NULL ELSE qa.is_correct END,
+                'points_earned', CASE WHEN a.status = 'in_progress' THEN NULL ELSE qa.points_earned END,
                 'created_at', qa.created_at,
                 'updated_at', qa.updated_at
              ) FROM quiz_answers qa
              JOIN questions q ON qa.question_id = q.id
+             JOIN quiz_attempts a ON qa.attempt_id = a.id
              WHERE qa.attempt_id = ?1
              ORDER BY q.order_index ASC",
         )
@@ -979,41 +1868,28 @@ pub fn calculate_attempt_score(db_path: String, attempt_id: String) -> Result<St
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
-    let score_json: String = conn
+    let (student_id, status): (String, Option<String>) = conn
         .query_row(
-            "SELECT json_object(
-                'total_questions', COALESCE(COUNT(*), 0),
-                'correct_answers', COALESCE(SUM(CASE WHEN is_correct = 1 THEN 1 ELSE 0 END), 0),
-                'points_earned', COALESCE(SUM(points_earned), 0.0),
-                'points_possible', COALESCE((
-                    SELECT SUM(q.points)
-                    FROM questions q
-                    WHERE q.quiz_id = (
-                        SELECT quiz_id FROM quiz_attempts WHERE id = ?1
-                    )
-                ), 0.0),
-                'percentage', COALESCE(
-                    ROUND(
-                        CAST(COALESCE(SUM(points_earned), 0.0) AS FLOAT) /
-                        NULLIF(COALESCE((
-                            SELECT SUM(q.points)
-                            FROM questions q
-                            WHERE q.quiz_id = (
-                                SELECT quiz_id FROM quiz_attempts WHERE id = ?1
-                            )
-                        ), 0.0), 0.0) * 100,
-                        2
-                    ),
-                    0.0
-                )
-             ) FROM quiz_answers
-             WHERE attempt_id = ?1",
+            "SELECT student_id, status FROM quiz_attempts WHERE id = ?1",
             params![attempt_id],
-            |row| row.get(0),
+            |row| Ok((row.get(0)?, row.get(1)?)),
         )
-        .map_err(|e| format!("Failed to calculate score: {}", e))?;
+        .map_err(|e| format!("Attempt not found: {}", e))?;
+
+    access::ensure_current_student(&conn, &student_id)?;
 
-    Ok(score_json)
+    // ✅ A running score would reveal which answers are right, and grading
+    // writes is_correct into quiz_answers, so both wait for the submission
+    if !matches!(status.as_deref(), Some("completed") | Some("submitted")) {
+        return Err(format!(
+            "Attempt {} has not been submitted; its score is available afterwards",
+            attempt_id
+        ));
+    }
+
+    let grade = assessment::grade_attempt(&conn, &attempt_id)?;
+
+    Ok(grade.to_json().to_string())
 }
 
 #[tauri::command]
@@ -1025,6 +1901,8 @@ pub fn get_best_quiz_score(
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
+    access::ensure_current_student(&conn, &student_id)?;
+
     let best_score: Option<f64> = conn
         .query_row(
             "SELECT MAX(score) FROM quiz_attempts
@@ -0,0 +1,420 @@
+use crate::commands::{access, assessment, get_connection};
+use rusqlite::{params, Connection};
+use serde_json::Value as JsonValue;
+
+// ============================================================================
+// REVIEW CARDS
+// ============================================================================
+// Every question answered wrong in a completed attempt becomes a review card.
+// Cards are rescheduled with SM-2: answer quality 0-5, where anything below 3
+// counts as a lapse and sends the card back to the start.
+
+const MIN_EASE_FACTOR: f64 = 1.3;
+// Keeps a long run of easy answers (or a corrupt row) from pushing a card
+// past what chrono can represent
+pub const MAX_INTERVAL_DAYS: i64 = 365 * 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Schedule {
+    pub ease_factor: f64,
+    pub interval_days: i64,
+    pub repetitions: i64,
+    pub lapses: i64,
+}
+
+pub fn next_schedule(current: &Schedule, quality: i64) -> Schedule {
+    let quality = quality.clamp(0, 5);
+    let penalty = (5 - quality) as f64;
+    let ease_factor =
+        (current.ease_factor + 0.1 - penalty * (0.08 + penalty * 0.02)).max(MIN_EASE_FACTOR);
+
+    if quality < 3 {
+        return Schedule {
+            ease_factor,
+            interval_days: 1,
+            repetitions: 0,
+            lapses: current.lapses + 1,
+        };
+    }
+
+    let repetitions = current.repetitions + 1;
+    let interval_days = match repetitions {
+        1 => 1,
+        2 => 6,
+        _ => ((current.interval_days as f64 * current.ease_factor).round() as i64)
+            .clamp(1, MAX_INTERVAL_DAYS),
+    };
+
+    Schedule {
+        ease_factor,
+        interval_days,
+        repetitions,
+        lapses: current.lapses,
+    }
+}
+
+// Wrong answers from completed attempts, shaped as new review_cards rows
+const WRONG_ANSWER_CARDS: &str = "SELECT 'rc_' || qa.student_id || '_' || ans.question_id,
+        qa.student_id, ans.question_id, qa.quiz_id, COALESCE(q.course_id, m.course_id),
+        ?1, qa.id, ?1, ?1
+ FROM quiz_answers ans
+ JOIN quiz_attempts qa ON ans.attempt_id = qa.id
+ JOIN quizzes q ON qa.quiz_id = q.id
+ LEFT JOIN modules m ON q.module_id = m.id
+ WHERE qa.status = 'completed' AND COALESCE(ans.is_correct, 0) = 0";
+
+const INSERT_CARD: &str = "INSERT INTO review_cards
+ (id, student_id, question_id, quiz_id, course_id, due_at, source_attempt_id, created_at, updated_at)";
+
+// Missing a question again in a new attempt is a lapse: the card restarts
+// and is due immediately
+pub fn seed_from_attempt(conn: &Connection, attempt_id: &str) -> Result<usize, String> {
+    conn.execute(
+        &format!(
+            "{} {} AND qa.id = ?2
+             ON CONFLICT(student_id, question_id) DO UPDATE SET
+                repetitions = 0,
+                interval_days = 0,
+                lapses = lapses + 1,
+                due_at = excluded.due_at,
+                source_attempt_id = excluded.source_attempt_id,
+                updated_at = excluded.updated_at
+             WHERE review_cards.source_attempt_id IS NOT excluded.source_attempt_id",
+            INSERT_CARD, WRONG_ANSWER_CARDS
+        ),
+        params![chrono::Utc::now().to_rfc3339(), attempt_id],
+    )
+    .map_err(|e| format!("Failed to seed review cards: {}", e))
+}
+
+// Picks up wrong answers recorded before review cards existed
+fn seed_missing_cards(conn: &Connection, student_id: &str) -> Result<usize, String> {
+    conn.execute(
+        &format!(
+            "{} {} AND qa.student_id = ?2
+             ON CONFLICT(student_id, question_id) DO NOTHING",
+            INSERT_CARD, WRONG_ANSWER_CARDS
+        ),
+        params![chrono::Utc::now().to_rfc3339(), student_id],
+    )
+    .map_err(|e| format!("Failed to seed review cards: {}", e))
+}
+
+const CARD_JSON: &str = "json_object(
+    'id', rc.id,
+    'student_id', rc.student_id,
+    'question_id', rc.question_id,
+    'quiz_id', rc.quiz_id,
+    'course_id', rc.course_id,
+    'ease_factor', rc.ease_factor,
+    'interval_days', rc.interval_days,
+    'repetitions', rc.repetitions,
+    'lapses', rc.lapses,
+    'review_count', rc.review_count,
+    'due_at', rc.due_at,
+    'last_reviewed_at', rc.last_reviewed_at,
+    'last_quality', rc.last_quality
+)";
+
+fn load_card(conn: &Connection, card_id: &str) -> Result<JsonValue, String> {
+    let json: String = conn
+        .query_row(
+            &format!("SELECT {} FROM review_cards rc WHERE rc.id = ?1", CARD_JSON),
+            params![card_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Review card not found: {}", e))?;
+
+    serde_json::from_str(&json).map_err(|e| format!("Invalid review card: {}", e))
+}
+
+// Options are listed without their correctness; the key is only revealed
+// by record_review_result. Short-answer questions list none, since their
+// correct options are the accepted answers.
+fn load_options(conn: &Connection, question_id: &str) -> Result<Vec<JsonValue>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT o.id, o.option_text FROM question_options o
+             JOIN questions q ON o.question_id = q.id
+             WHERE o.question_id = ?1
+               AND COALESCE(q.question_type, 'single_choice') != 'short_answer'
+             ORDER BY o.order_index ASC, o.id ASC",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let options = stmt
+        .query_map(params![question_id], |row| {
+            Ok(serde_json::json!({
+                "id": row.get::<_, String>(0)?,
+                "option_text": row.get::<_, String>(1)?
+            }))
+        })
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(options)
+}
+
+#[tauri::command]
+pub fn get_due_reviews(
+    db_path: String,
+    student_id: String,
+    course_id: Option<String>,
+    limit: Option<i64>,
+) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    access::ensure_current_student(&conn, &student_id)?;
+
+    seed_missing_cards(&conn, &student_id)?;
+
+    let now = chrono::Utc::now().to_rfc3339();
+
+    // Cards whose question has since been removed from the device are skipped
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {}, q.id, q.question_text, COALESCE(q.question_type, 'single_choice'), q.image_url
+             FROM review_cards rc
+             JOIN questions q ON rc.question_id = q.id
+             WHERE rc.student_id = ?1
+               AND (?2 IS NULL OR rc.course_id = ?2)
+               AND datetime(rc.due_at) <= datetime(?3)
+             ORDER BY rc.due_at ASC, rc.id ASC
+             LIMIT ?4",
+            CARD_JSON
+        ))
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let rows: Vec<(String, String, String, String, Option<String>)> = stmt
+        .query_map(
+            params![student_id, course_id, now, limit.unwrap_or(20)],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        )
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut cards = Vec::new();
+    for (card_json, question_id, question_text, question_type, image_url) in rows {
+        let mut card: JsonValue =
+            serde_json::from_str(&card_json).map_err(|e| format!("Invalid review card: {}", e))?;
+
+        card["question"] = serde_json::json!({
+            "id": question_id,
+            "question_text": question_text,
+            "question_type": question_type,
+            "image_url": image_url,
+            "options": load_options(&conn, &question_id)?
+        });
+        cards.push(card);
+    }
+
+    let (due_count, total_cards, next_due_at): (i64, i64, Option<String>) = conn
+        .query_row(
+            "SELECT COUNT(CASE WHEN datetime(rc.due_at) <= datetime(?3) THEN 1 END),
+                    COUNT(*),
+                    MIN(CASE WHEN datetime(rc.due_at) > datetime(?3) THEN rc.due_at END)
+             FROM review_cards rc
+             JOIN questions q ON rc.question_id = q.id
+             WHERE rc.student_id = ?1 AND (?2 IS NULL OR rc.course_id = ?2)",
+            params![student_id, course_id, now],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| format!("Failed to count review cards: {}", e))?;
+
+    let reviews = serde_json::json!({
+        "due_count": due_count,
+        "total_cards": total_cards,
+        "next_due_at": next_due_at,
+        "cards": cards
+    });
+
+    Ok(reviews.to_string())
+}
+
+// result_data carries either a self-rated "quality" (0-5) or an answer in the
+// same shape as a quiz answer, which is graded against the answer key
+#[tauri::command]
+pub fn record_review_result(
+    db_path: String,
+    card_id: String,
+    result_data: String,
+) -> Result<String, String> {
+    let conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let result: JsonValue = serde_json::from_str(&result_data)
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let (student_id, question_id, quiz_id, current): (String, String, String, Schedule) = conn
+        .query_row(
+            "SELECT student_id, question_id, quiz_id, ease_factor, interval_days, repetitions, lapses
+             FROM review_cards WHERE id = ?1",
+            params![card_id],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    Schedule {
+                        ease_factor: row.get(3)?,
+                        interval_days: row.get(4)?,
+                        repetitions: row.get(5)?,
+                        lapses: row.get(6)?,
+                    },
+                ))
+            },
+        )
+        .map_err(|e| format!("Review card not found: {}", e))?;
+
+    access::ensure_current_student(&conn, &student_id)?;
+
+    let graded = match result["quality"].as_i64() {
+        Some(_) => None,
+        None => Some(assessment::grade_question(
+            &conn,
+            &quiz_id,
+            &question_id,
+            &assessment::AnswerInput::from_json(&result),
+        )?),
+    };
+
+    let quality = match (&graded, result["quality"].as_i64()) {
+        (_, Some(quality)) if (0..=5).contains(&quality) => quality,
+        (_, Some(quality)) => return Err(format!("Quality must be between 0 and 5, got {}", quality)),
+        (Some(answer), None) if answer.is_correct => 4,
+        _ => 1,
+    };
+
+    let next = next_schedule(&current, quality);
+    let now = chrono::Utc::now();
+    let due_at = now
+        .checked_add_signed(chrono::Duration::days(next.interval_days))
+        .ok_or_else(|| format!("Review interval out of range: {} days", next.interval_days))?
+        .to_rfc3339();
+    let now = now.to_rfc3339();
+
+    conn.execute(
+        "UPDATE review_cards
+         SET ease_factor = ?1, interval_days = ?2, repetitions = ?3, lapses = ?4,
+             review_count = review_count + 1, due_at = ?5, last_reviewed_at = ?6,
+             last_quality = ?7, updated_at = ?6
+         WHERE id = ?8",
+        params![
+            next.ease_factor,
+            next.interval_days,
+            next.repetitions,
+            next.lapses,
+            due_at,
+            now,
+            quality,
+            card_id
+        ],
+    )
+    .map_err(|e| format!("Failed to update review card: {}", e))?;
+
+    // Self-study, so the key is shown as soon as the card is answered
+    let correct_option_ids: Vec<String> = conn
+        .prepare("SELECT id FROM question_options WHERE question_id = ?1 AND is_correct = 1 ORDER BY order_index")
+        .and_then(|mut stmt| {
+            stmt.query_map(params![question_id], |row| row.get(0))?
+                .collect()
+        })
+        .map_err(|e| format!("Failed to load answer key: {}", e))?;
+
+    let accepted_answers: JsonValue = conn
+        .query_row(
+            "SELECT accepted_answers FROM questions WHERE id = ?1",
+            params![question_id],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_else(|| JsonValue::Array(Vec::new()));
+
+    let review = serde_json::json!({
+        "card": load_card(&conn, &card_id)?,
+        "quality": quality,
+        "is_correct": graded.as_ref().map(|g| g.is_correct),
+        "correct_option_ids": correct_option_ids,
+        "accepted_answers": accepted_answers
+    });
+
+    Ok(review.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_card() -> Schedule {
+        Schedule {
+            ease_factor: 2.5,
+            interval_days: 0,
+            repetitions: 0,
+            lapses: 0,
+        }
+    }
+
+    #[test]
+    fn good_answers_grow_the_interval() {
+        let first = next_schedule(&new_card(), 4);
+        assert_eq!((first.interval_days, first.repetitions), (1, 1));
+        let second = next_schedule(&first, 4);
+        assert_eq!((second.interval_days, second.repetitions), (6, 2));
+        let third = next_schedule(&second, 4);
+        assert_eq!((third.interval_days, third.repetitions), (15, 3));
+        assert_eq!(third.ease_factor, 2.5);
+    }
+
+    #[test]
+    fn a_lapse_restarts_the_card() {
+        let card = Schedule {
+            ease_factor: 2.5,
+            interval_days: 40,
+            repetitions: 5,
+            lapses: 1,
+        };
+        let next = next_schedule(&card, 1);
+        assert_eq!((next.interval_days, next.repetitions, next.lapses), (1, 0, 2));
+        assert!(next.ease_factor < card.ease_factor);
+    }
+
+    #[test]
+    fn ease_factor_has_a_floor() {
+        let mut card = new_card();
+        for _ in 0..20 {
+            card = next_schedule(&card, 0);
+        }
+        assert_eq!(card.ease_factor, MIN_EASE_FACTOR);
+    }
+
+    #[test]
+    fn quality_outside_the_scale_is_clamped() {
+        assert_eq!(next_schedule(&new_card(), 9), next_schedule(&new_card(), 5));
+        assert_eq!(next_schedule(&new_card(), -3), next_schedule(&new_card(), 0));
+    }
+
+    #[test]
+    fn interval_is_capped() {
+        let mut card = new_card();
+        for _ in 0..50 {
+            card = next_schedule(&card, 5);
+        }
+        assert_eq!(card.interval_days, MAX_INTERVAL_DAYS);
+
+        let corrupt = Schedule {
+            ease_factor: 2.5,
+            interval_days: i64::MAX,
+            repetitions: 3,
+            lapses: 0,
+        };
+        assert_eq!(next_schedule(&corrupt, 5).interval_days, MAX_INTERVAL_DAYS);
+        assert!(chrono::Utc::now()
+            .checked_add_signed(chrono::Duration::days(MAX_INTERVAL_DAYS))
+            .is_some());
+    }
+}
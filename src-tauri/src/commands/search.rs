@@ -0,0 +1,496 @@
+use crate::commands::get_connection;
+use rusqlite::{params, Connection};
+use serde_json::Value as JsonValue;
+
+// ============================================================================
+// SEARCH INDEX
+// ============================================================================
+// search_index is an FTS5 table with one row per course, module, content
+// block and question. The save commands re-index each record they write, so
+// the index works offline and never needs a full rebuild in normal use.
+
+const SEARCH_KINDS: [(&str, &str); 4] = [
+    ("courses", "course"),
+    ("modules", "module"),
+    ("content", "content"),
+    ("questions", "question"),
+];
+
+// Title matches count ten times as much as body matches
+const RANK: &str = "bm25(search_index, 10.0, 1.0)";
+
+// Keys inside content_data that hold identifiers or links, not prose
+fn is_metadata_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    key == "id"
+        || key.ends_with("_id")
+        || key.ends_with("url")
+        || matches!(
+            key.as_str(),
+            "type" | "src" | "href" | "mime_type" | "filename" | "format" | "checksum"
+        )
+}
+
+// Drops tags and decodes the common entities so snippets read as plain text
+fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn collect_text(value: &JsonValue, key: &str, out: &mut Vec<String>) {
+    match value {
+        JsonValue::String(s) if !is_metadata_key(key) && !s.starts_with("http") => {
+            let text = plain_text(s);
+            if !text.is_empty() {
+                out.push(text);
+            }
+        }
+        JsonValue::Array(items) => items.iter().for_each(|item| collect_text(item, key, out)),
+        JsonValue::Object(fields) => fields.iter().for_each(|(k, v)| collect_text(v, k, out)),
+        _ => {}
+    }
+}
+
+// content_data is stored as JSON text; anything else is indexed as-is
+pub fn content_text(content_data: &str) -> String {
+    match serde_json::from_str::<JsonValue>(content_data) {
+        Ok(value) => {
+            let mut parts = Vec::new();
+            collect_text(&value, "", &mut parts);
+            parts.join(" ")
+        }
+        Err(_) => plain_text(content_data),
+    }
+}
+
+// (module_id, course_id, title, content_data)
+type ContentRow = (Option<String>, Option<String>, Option<String>, Option<String>);
+
+// (question_text, quiz_title, module_id, course_id, option texts)
+type QuestionRow = (String, Option<String>, Option<String>, Option<String>, Option<String>);
+
+type Indexer = fn(&Connection, &str) -> Result<(), String>;
+
+fn replace_entry(
+    conn: &Connection,
+    kind: &str,
+    ref_id: &str,
+    course_id: Option<&str>,
+    module_id: Option<&str>,
+    title: &str,
+    body: &str,
+) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM search_index WHERE kind = ?1 AND ref_id = ?2",
+        params![kind, ref_id],
+    )
+    .map_err(|e| format!("Failed to update search index: {}", e))?;
+
+    conn.execute(
+        "INSERT INTO search_index (title, body, kind, ref_id, course_id, module_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![title, body, kind, ref_id, course_id, module_id],
+    )
+    .map_err(|e| format!("Failed to update search index: {}", e))?;
+
+    Ok(())
+}
+
+// Removes the entry when the record is gone
+fn remove_entry(conn: &Connection, kind: &str, ref_id: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM search_index WHERE kind = ?1 AND ref_id = ?2",
+        params![kind, ref_id],
+    )
+    .map_err(|e| format!("Failed to update search index: {}", e))?;
+
+    Ok(())
+}
+
+pub fn index_course(conn: &Connection, course_id: &str) -> Result<(), String> {
+    let course: Option<(String, Option<String>)> = conn
+        .query_row(
+            "SELECT title, description FROM courses WHERE id = ?1",
+            params![course_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok();
+
+    match course {
+        Some((title, description)) => replace_entry(
+            conn,
+            "course",
+            course_id,
+            Some(course_id),
+            None,
+            &title,
+            &plain_text(&description.unwrap_or_default()),
+        ),
+        None => remove_entry(conn, "course", course_id),
+    }
+}
+
+// Content saved before its module could not be tied to a course yet, so the
+// module's blocks pick up its course here
+pub fn index_module(conn: &Connection, module_id: &str) -> Result<(), String> {
+    let module: Option<(Option<String>, Option<String>, Option<String>)> = conn
+        .query_row(
+            "SELECT course_id, title, description FROM modules WHERE id = ?1",
+            params![module_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .ok();
+
+    let Some((course_id, title, description)) = module else {
+        return remove_entry(conn, "module", module_id);
+    };
+
+    replace_entry(
+        conn,
+        "module",
+        module_id,
+        course_id.as_deref(),
+        Some(module_id),
+        &title.unwrap_or_default(),
+        &plain_text(&description.unwrap_or_default()),
+    )?;
+
+    conn.execute(
+        "UPDATE search_index SET course_id = ?1 WHERE kind = 'content' AND module_id = ?2",
+        params![course_id, module_id],
+    )
+    .map_err(|e| format!("Failed to update search index: {}", e))?;
+
+    Ok(())
+}
+
+pub fn index_content_block(conn: &Connection, content_id: &str) -> Result<(), String> {
+    let content: Option<ContentRow> = conn
+        .query_row(
+            "SELECT cb.module_id, m.course_id, cb.title, cb.content_data
+             FROM content_blocks cb
+             LEFT JOIN modules m ON cb.module_id = m.id
+             WHERE cb.id = ?1",
+            params![content_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .ok();
+
+    let Some((module_id, course_id, title, content_data)) = content else {
+        return remove_entry(conn, "content", content_id);
+    };
+
+    replace_entry(
+        conn,
+        "content",
+        content_id,
+        course_id.as_deref(),
+        module_id.as_deref(),
+        &title.unwrap_or_default(),
+        &content_text(&content_data.unwrap_or_default()),
+    )
+}
+
+// Questions are titled with their quiz so a hit shows where it came from;
+// options are searchable text but the answer key is never indexed
+pub fn index_question(conn: &Connection, question_id: &str) -> Result<(), String> {
+    let question: Option<QuestionRow> = conn
+        .query_row(
+            "SELECT q.question_text, qz.title, qz.module_id, COALESCE(qz.course_id, m.course_id),
+                    (SELECT group_concat(option_text, ' ') FROM question_options WHERE question_id = q.id)
+             FROM questions q
+             LEFT JOIN quizzes qz ON q.quiz_id = qz.id
+             LEFT JOIN modules m ON qz.module_id = m.id
+             WHERE q.id = ?1",
+            params![question_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        )
+        .ok();
+
+    let Some((question_text, quiz_title, module_id, course_id, options)) = question else {
+        return remove_entry(conn, "question", question_id);
+    };
+
+    let body = match options {
+        Some(options) => format!("{} {}", plain_text(&question_text), options),
+        None => plain_text(&question_text),
+    };
+
+    replace_entry(
+        conn,
+        "question",
+        question_id,
+        course_id.as_deref(),
+        module_id.as_deref(),
+        &quiz_title.unwrap_or_default(),
+        &body,
+    )
+}
+
+// Picks up a quiz's course and module for questions saved before it
+pub fn index_quiz(conn: &Connection, quiz_id: &str) -> Result<(), String> {
+    for question_id in ids(conn, "SELECT id FROM questions WHERE quiz_id = ?1", params![quiz_id])? {
+        index_question(conn, &question_id)?;
+    }
+
+    Ok(())
+}
+
+// Drops modules, content and questions of a course whose content was removed
+// from the device; the course itself stays searchable
+pub fn remove_course_content(conn: &Connection, course_id: &str) -> Result<usize, String> {
+    conn.execute(
+        "DELETE FROM search_index
+         WHERE kind != 'course'
+           AND (course_id = ?1 OR module_id IN (SELECT id FROM modules WHERE course_id = ?1))",
+        params![course_id],
+    )
+    .map_err(|e| format!("Failed to update search index: {}", e))
+}
+
+fn ids<P: rusqlite::Params>(conn: &Connection, sql: &str, params: P) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let ids = stmt
+        .query_map(params, |row| row.get(0))
+        .map_err(|e| format!("Query failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(ids)
+}
+
+fn rebuild_index(conn: &Connection) -> Result<usize, String> {
+    conn.execute("DELETE FROM search_index", [])
+        .map_err(|e| format!("Failed to clear search index: {}", e))?;
+
+    let indexers: [(&str, Indexer); 4] = [
+        ("SELECT id FROM courses", index_course),
+        ("SELECT id FROM modules", index_module),
+        ("SELECT id FROM content_blocks", index_content_block),
+        ("SELECT id FROM questions", index_question),
+    ];
+
+    let mut count = 0;
+    for (sql, index) in indexers {
+        for id in ids(conn, sql, [])? {
+            index(conn, &id)?;
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+// ============================================================================
+// SEARCH COMMANDS
+// ============================================================================
+
+// Every word of the query must match the start of some indexed word, in any
+// order: "photo" finds "photosynthesis" and "photo synth" finds "synthetic
+// photo paper", but "synth" alone does not find "photosynthesis". Punctuation
+// is dropped, which keeps user input from being read as FTS5 query syntax.
+fn match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{}\"*", term))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+#[tauri::command]
+pub fn search(
+    db_path: String,
+    query: String,
+    scope: Option<String>,
+    limit: Option<i64>,
+) -> Result<String, String> {
+    let mut conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let kind = match scope.as_deref().unwrap_or("all") {
+        "all" => None,
+        scope => Some(
+            SEARCH_KINDS
+                .iter()
+                .find(|(name, _)| *name == scope)
+                .map(|(_, kind)| *kind)
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown search scope '{}'. Expected all, courses, modules, content or questions",
+                        scope
+                    )
+                })?,
+        ),
+    };
+
+    let Some(expression) = match_expression(&query) else {
+        return Ok(serde_json::json!({ "query": query, "total": 0, "results": [] }).to_string());
+    };
+
+    // Databases created before the index existed are indexed on first use
+    let (indexed, courses): (i64, i64) = conn
+        .query_row(
+            "SELECT (SELECT COUNT(*) FROM search_index), (SELECT COUNT(*) FROM courses)",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Failed to read search index: {}", e))?;
+
+    if indexed == 0 && courses > 0 {
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let count = rebuild_index(&tx)?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit search index: {}", e))?;
+        println!("🔎 Search index built ({} entries)", count);
+    }
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT json_object(
+                'kind', search_index.kind,
+                'id', search_index.ref_id,
+                'course_id', search_index.course_id,
+                'course_title', c.title,
+                'module_id', search_index.module_id,
+                'module_title', m.title,
+                'quiz_id', q.quiz_id,
+                'title', search_index.title,
+                'title_highlighted', highlight(search_index, 0, '<mark>', '</mark>'),
+                'snippet', snippet(search_index, 1, '<mark>', '</mark>', '…', 16),
+                'score', round(-{rank}, 4)
+             )
+             FROM search_index
+             LEFT JOIN courses c ON search_index.course_id = c.id
+             LEFT JOIN modules m ON search_index.module_id = m.id
+             LEFT JOIN questions q ON search_index.kind = 'question' AND search_index.ref_id = q.id
+             WHERE search_index MATCH ?1 AND (?2 IS NULL OR search_index.kind = ?2)
+             ORDER BY {rank}
+             LIMIT ?3",
+            rank = RANK
+        ))
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let results: Vec<JsonValue> = stmt
+        .query_map(params![expression, kind, limit.unwrap_or(20)], |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| format!("Search failed: {}", e))?
+        .filter_map(|r| r.ok())
+        .filter_map(|json| serde_json::from_str(&json).ok())
+        .collect();
+
+    let response = serde_json::json!({
+        "query": query,
+        "total": results.len(),
+        "results": results
+    });
+
+    Ok(response.to_string())
+}
+
+#[tauri::command]
+pub fn rebuild_search_index(db_path: String) -> Result<String, String> {
+    let mut conn = get_connection(&db_path)
+        .map_err(|e| format!("Database connection failed: {}", e))?;
+
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let count = rebuild_index(&tx)?;
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit search index: {}", e))?;
+
+    println!("🔎 Search index rebuilt ({} entries)", count);
+
+    Ok(format!("{} records indexed", count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_word_becomes_a_prefix_term() {
+        assert_eq!(match_expression("photo"), Some("\"photo\"*".to_string()));
+        assert_eq!(
+            match_expression("  photo   synth "),
+            Some("\"photo\"* \"synth\"*".to_string())
+        );
+    }
+
+    #[test]
+    fn punctuation_is_not_query_syntax() {
+        assert_eq!(
+            match_expression("cell\"s OR NOT (wall)*"),
+            Some("\"cell\"* \"s\"* \"OR\"* \"NOT\"* \"wall\"*".to_string())
+        );
+        assert_eq!(match_expression("c-4"), Some("\"c\"* \"4\"*".to_string()));
+    }
+
+    #[test]
+    fn non_ascii_words_are_kept() {
+        assert_eq!(match_expression("élève"), Some("\"élève\"*".to_string()));
+    }
+
+    #[test]
+    fn nothing_searchable_gives_no_expression() {
+        assert_eq!(match_expression(""), None);
+        assert_eq!(match_expression("  ?! -- "), None);
+    }
+
+    #[test]
+    fn prefixes_match_the_start_of_words_only() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE t USING fts5(body, tokenize = 'unicode61 remove_diacritics 2');
+             INSERT INTO t (body) VALUES ('photosynthesis'), ('synthetic photo paper');",
+        )
+        .unwrap();
+        let hits = |query: &str| -> i64 {
+            conn.query_row(
+                "SELECT COUNT(*) FROM t WHERE t MATCH ?1",
+                [match_expression(query).unwrap()],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(hits("photo"), 2);
+        assert_eq!(hits("photo synth"), 1);
+        assert_eq!(hits("synth"), 1);
+    }
+}
@@ -1,5 +1,23 @@
 use crate::commands::get_connection;
-use rusqlite::params;
+use rusqlite::{params, Connection};
+
+// Queues a locally made change for upload on the next sync
+pub fn queue_change(
+    conn: &Connection,
+    operation_type: &str,
+    table_name: &str,
+    record_id: &str,
+    data: &str,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO sync_queue (operation_type, table_name, record_id, data, created_at, retry_count)
+         VALUES (?1, ?2, ?3, ?4, datetime('now'), 0)",
+        params![operation_type, table_name, record_id, data],
+    )
+    .map_err(|e| format!("Failed to add to sync queue: {}", e))?;
+
+    Ok(())
+}
 
 // ============================================================================
 // SYNC QUEUE COMMANDS
@@ -16,12 +34,7 @@ pub fn add_to_sync_queue(
     let conn = get_connection(&db_path)
         .map_err(|e| format!("Database connection failed: {}", e))?;
 
-    conn.execute(
-        "INSERT INTO sync_queue (operation_type, table_name, record_id, data, created_at, retry_count)
-         VALUES (?1, ?2, ?3, ?4, datetime('now'), 0)",
-        params![operation_type, table_name, record_id, data],
-    )
-    .map_err(|e| format!("Failed to add to sync queue: {}", e))?;
+    queue_change(&conn, &operation_type, &table_name, &record_id, &data)?;
 
     Ok("Added to sync queue successfully".to_string())
 }
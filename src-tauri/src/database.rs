@@ -2,6 +2,31 @@ use tauri::{AppHandle, Manager};
 use std::fs;
 use rusqlite::{Connection, Result};
 
+// Incremental migrations applied once each on top of 001_initial.sql.
+// The number is the schema_version recorded in app_metadata after it runs.
+const MIGRATIONS: &[(i64, &str)] = &[
+    (4, include_str!("../migrations/002_media_encryption.sql")),
+    (5, include_str!("../migrations/003_offline_media_size.sql")),
+    (6, include_str!("../migrations/004_quiz_deadlines.sql")),
+    (7, include_str!("../migrations/005_attempt_question_order.sql")),
+    (8, include_str!("../migrations/006_quiz_review_settings.sql")),
+    (9, include_str!("../migrations/007_question_types.sql")),
+    (10, include_str!("../migrations/008_course_prerequisites.sql")),
+    (11, include_str!("../migrations/009_course_access_policies.sql")),
+    (12, include_str!("../migrations/010_provisional_certificates.sql")),
+    (13, include_str!("../migrations/011_learning_sessions.sql")),
+    (14, include_str!("../migrations/012_resume_positions.sql")),
+    (15, include_str!("../migrations/013_review_cards.sql")),
+    (16, include_str!("../migrations/014_activity_log.sql")),
+    (17, include_str!("../migrations/015_bookmarks_notes.sql")),
+    (18, include_str!("../migrations/016_search_index.sql")),
+    (19, include_str!("../migrations/017_course_catalog_indexes.sql")),
+    (20, include_str!("../migrations/018_active_user.sql")),
+    (21, include_str!("../migrations/019_learner_profiles.sql")),
+    (22, include_str!("../migrations/020_offline_credentials.sql")),
+    (23, include_str!("../migrations/021_offline_grace_period.sql")),
+];
+
 pub fn get_database_path(app: &AppHandle) -> Result<String, String> {
     let app_data_dir = app.path()
         .app_data_dir()
@@ -30,7 +55,39 @@ pub fn initialize_database(app: &AppHandle) -> Result<(), String> {
     conn.execute_batch(migration_sql)
         .map_err(|e| format!("Failed to execute migration: {}", e))?;
 
+    run_migrations(&conn)?;
+
     println!("Database created successfully at: {}", db_path);
 
     Ok(())
 }
+
+fn run_migrations(conn: &Connection) -> Result<(), String> {
+    let current_version: i64 = conn
+        .query_row(
+            "SELECT CAST(value AS INTEGER) FROM app_metadata WHERE key = 'schema_version'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to read schema version: {}", e))?;
+
+    for (version, sql) in MIGRATIONS.iter().filter(|(v, _)| *v > current_version) {
+        println!("Applying migration to schema version {}", version);
+
+        // The migration and its version bump commit together
+        conn.execute_batch(&format!(
+            "BEGIN;
+             {}
+             UPDATE app_metadata SET value = '{}', updated_at = CURRENT_TIMESTAMP
+             WHERE key = 'schema_version';
+             COMMIT;",
+            sql, version
+        ))
+        .map_err(|e| {
+            let _ = conn.execute_batch("ROLLBACK;");
+            format!("Failed to apply migration {}: {}", version, e)
+        })?;
+    }
+
+    Ok(())
+}
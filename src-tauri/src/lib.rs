@@ -1,5 +1,8 @@
 mod database;
 mod commands;
+mod certificate;
+mod media;
+mod media_crypto;
 
 use tauri::Manager;
 
@@ -34,6 +37,23 @@ pub fn run() {
       // Initialize database
       database::initialize_database(&app.handle())?;
 
+      let db_path = database::get_database_path(&app.handle())?;
+      let conn = commands::get_connection(&db_path)?;
+
+      // Seal media keys that earlier versions stored in the clear
+      if let Err(e) = media_crypto::seal_legacy_session_keys(&conn) {
+        log::warn!("Failed to seal stored media keys: {}", e);
+      }
+
+      // Submit timed quiz attempts that ran out while the app was closed
+      match commands::assessment::finalize_overdue_attempts(&conn) {
+        Ok((_, failed)) if failed > 0 => {
+          log::warn!("{} overdue quiz attempts could not be submitted", failed);
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!("Failed to finalize overdue quiz attempts: {}", e),
+      }
+
       // Get the window
       let window = app.get_webview_window("main").unwrap();
 
@@ -48,6 +68,13 @@ pub fn run() {
 
       Ok(())
     })
+    .register_asynchronous_uri_scheme_protocol(media::MEDIA_SCHEME, |ctx, request, responder| {
+      // Serve cached course media off the main thread so large reads don't stall the UI
+      let app = ctx.app_handle().clone();
+      std::thread::spawn(move || {
+        responder.respond(media::handle_media_request(&app, &request));
+      });
+    })
     .plugin(tauri_plugin_sql::Builder::default().build())
     .plugin(tauri_plugin_shell::init())  // ← ADD THIS LINE (line 52)
     .invoke_handler(tauri::generate_handler![
@@ -59,20 +86,30 @@ pub fn run() {
       commands::auth::get_auth_tokens,
       commands::auth::clear_auth_tokens,
       commands::auth::check_token_expired,
+      commands::auth::save_offline_credential,
       commands::auth::save_user,
       commands::auth::get_current_user,
       commands::auth::get_user_by_email,
 
+      // ========== PROFILE COMMANDS ==========
+      commands::profiles::list_profiles,
+      commands::profiles::switch_profile,
+      commands::profiles::remove_profile,
+
       // ========== COURSE COMMANDS ==========
       commands::courses::save_course,
       commands::courses::save_courses_bulk,
       commands::courses::get_all_courses,
+      commands::courses::query_courses,
       commands::courses::get_enrolled_courses,
       commands::courses::get_course_by_id,
       commands::courses::save_course_media,
       commands::courses::save_enrollment,
       commands::courses::get_user_enrollments,
       commands::courses::check_enrollment_exists,
+      commands::courses::save_course_prerequisites,
+      commands::courses::get_course_prerequisites,
+      commands::courses::check_course_eligibility,
 
       // ========== LESSON COMMANDS (Modules, Content, Quizzes, Questions) ==========
       commands::lessons::save_module,
@@ -96,6 +133,11 @@ pub fn run() {
       commands::progress::get_enrollment_progress,
       commands::progress::update_module_status,
       commands::progress::get_course_progress_summary,
+      commands::progress::evaluate_course_completion,
+      commands::progress::recompute_progress,
+      commands::progress::save_resume_position,
+      commands::progress::get_resume_position,
+      commands::progress::get_continue_learning,
       // Content Progress
       commands::progress::save_content_progress,
       commands::progress::get_content_progress,
@@ -111,6 +153,10 @@ pub fn run() {
       commands::progress::get_attempt_answers,
       commands::progress::calculate_attempt_score,
       commands::progress::get_best_quiz_score,
+      commands::assessment::grade_quiz_attempt,
+      commands::assessment::start_quiz_attempt,
+      commands::assessment::get_attempt_questions,
+      commands::assessment::get_attempt_review,
 
       // ========== OFFLINE COMMANDS (NEW) ==========
       commands::offline::save_offline_session,
@@ -126,11 +172,55 @@ pub fn run() {
       commands::offline::get_media_cache_by_media_id,
       commands::offline::update_media_download_progress,
       commands::offline::delete_media_cache_by_course,
+      commands::offline::remove_course_from_device,
       commands::offline::save_offline_progress_batch,
       commands::offline::get_unsynced_progress_batches,
       commands::offline::mark_batch_as_synced,
       commands::offline::delete_synced_progress_batches,
       commands::offline::get_offline_session_statistics,
+      commands::offline::get_course_storage_report,
+
+      // ========== ACCESS POLICY COMMANDS ==========
+      commands::access::check_course_offline_access,
+      commands::access::get_course_access_policy,
+      commands::access::get_module_access_map,
+
+      // ========== ACTIVITY COMMANDS ==========
+      commands::activity::start_learning_session,
+      commands::activity::heartbeat_learning_session,
+      commands::activity::stop_learning_session,
+      commands::activity::get_time_spent,
+      commands::activity::get_weekly_study_time,
+      commands::activity::get_activity_calendar,
+      commands::activity::get_learning_streak,
+      commands::activity::set_weekly_goal,
+      commands::activity::get_weekly_goal_progress,
+
+      // ========== REVIEW COMMANDS ==========
+      commands::review::get_due_reviews,
+      commands::review::record_review_result,
+
+      // ========== SEARCH COMMANDS ==========
+      commands::search::search,
+      commands::search::rebuild_search_index,
+
+      // ========== ANNOTATION COMMANDS ==========
+      commands::annotations::create_bookmark,
+      commands::annotations::update_bookmark,
+      commands::annotations::delete_bookmark,
+      commands::annotations::create_note,
+      commands::annotations::update_note,
+      commands::annotations::delete_note,
+      commands::annotations::get_course_annotations,
+      commands::annotations::get_content_annotations,
+      commands::annotations::export_course_notes,
+      commands::annotations::save_synced_annotations,
+
+      // ========== CERTIFICATE COMMANDS ==========
+      commands::certificates::generate_provisional_certificate,
+      commands::certificates::get_provisional_certificate,
+      commands::certificates::get_pending_provisional_certificates,
+      commands::certificates::replace_provisional_certificate,
 
       // ========== SYNC COMMANDS ==========
       commands::sync::add_to_sync_queue,
@@ -0,0 +1,446 @@
+use crate::commands::{access, get_connection};
+use crate::media_crypto::{self, MediaKey};
+use rusqlite::params;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tauri::http::{header, Method, Request, Response, StatusCode};
+use tauri::AppHandle;
+
+// ============================================================================
+// MEDIA STORE
+// ============================================================================
+
+pub const MEDIA_SCHEME: &str = "lms-media";
+
+// Largest slice served for a single range request, so playing or seeking
+// through a long video never loads the whole file into memory. Players ask
+// for the rest.
+const MAX_CHUNK_BYTES: u64 = 4 * 1024 * 1024;
+
+pub struct CachedMedia {
+    pub media_id: String,
+    pub media_type: String,
+    pub path: PathBuf,
+    // Present when the file is encrypted at rest
+    pub key: Option<MediaKey>,
+}
+
+// Downloaded course media lives next to the database:
+// <app_data_dir>/courses/<course_id>/media/<media_type>/<filename>
+pub fn get_media_store_dir(db_path: &str) -> Result<PathBuf, String> {
+    let app_data_dir = Path::new(db_path)
+        .parent()
+        .ok_or_else(|| format!("Invalid database path: {}", db_path))?;
+
+    Ok(app_data_dir.join("courses"))
+}
+
+// Returns the canonical path of a file only if it sits inside the media store
+pub fn ensure_in_media_store(db_path: &str, file_path: &str) -> Result<PathBuf, String> {
+    let store_dir = get_media_store_dir(db_path)?
+        .canonicalize()
+        .map_err(|e| format!("Media store not found: {}", e))?;
+
+    let path = Path::new(file_path)
+        .canonicalize()
+        .map_err(|e| format!("Media file not found: {}", e))?;
+
+    if !path.starts_with(&store_dir) {
+        return Err(format!("Media file is outside the media store: {}", file_path));
+    }
+
+    Ok(path)
+}
+
+pub fn resolve_cached_media(
+    db_path: &str,
+    media_id: &str,
+) -> Result<CachedMedia, (StatusCode, String)> {
+    let conn = get_connection(db_path).map_err(|e| {
+        (StatusCode::INTERNAL_SERVER_ERROR, format!("Database connection failed: {}", e))
+    })?;
+
+    let (course_id, media_type, local_file_path, encryption_session_id): (
+        String,
+        String,
+        String,
+        Option<String>,
+    ) = conn
+        .query_row(
+            "SELECT course_id, media_type, local_file_path, encryption_session_id FROM media_cache
+             WHERE media_id = ?1 AND is_downloaded = 1",
+            params![media_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|e| (StatusCode::NOT_FOUND, format!("Media cache not found: {}", e)))?;
+
+    access::ensure_content_access(&conn, &course_id).map_err(|e| (StatusCode::FORBIDDEN, e))?;
+
+    let path = ensure_in_media_store(db_path, &local_file_path)
+        .map_err(|e| (StatusCode::FORBIDDEN, e))?;
+
+    // ✅ Encrypted media is only readable while its offline session is valid
+    let key = match encryption_session_id {
+        Some(session_id) => {
+            let session_id = access::ensure_media_session_valid(&conn, &session_id)
+                .map_err(|e| (StatusCode::FORBIDDEN, e))?;
+            Some(
+                media_crypto::load_session_key(&conn, &session_id)
+                    .map_err(|e| (StatusCode::FORBIDDEN, e))?,
+            )
+        }
+        None => None,
+    };
+
+    Ok(CachedMedia {
+        media_id: media_id.to_string(),
+        media_type,
+        path,
+        key,
+    })
+}
+
+pub fn get_mime_type(path: &Path, media_type: &str) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "mp4" | "m4v" => "video/mp4",
+        "webm" => "video/webm",
+        "ogv" => "video/ogg",
+        "mov" => "video/quicktime",
+        "mkv" => "video/x-matroska",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "aac" => "audio/aac",
+        "wav" => "audio/wav",
+        "ogg" | "oga" => "audio/ogg",
+        "flac" => "audio/flac",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        _ => match media_type {
+            "video" => "video/mp4",
+            "audio" => "audio/mpeg",
+            "image" => "image/jpeg",
+            "document" => "application/pdf",
+            _ => "application/octet-stream",
+        },
+    }
+}
+
+// ============================================================================
+// RANGE REQUESTS
+// ============================================================================
+
+#[derive(Debug, PartialEq)]
+pub enum ByteRange {
+    // No usable Range header: the whole file is served with a 200
+    Whole,
+    // Inclusive byte positions, at most MAX_CHUNK_BYTES long
+    Partial(u64, u64),
+    // Answered with a 416
+    Unsatisfiable,
+}
+
+// Reads the first "bytes=" range of the header for a file of `file_size`
+// bytes. A missing or unreadable header asks for the whole file, as RFC 9110
+// has servers ignore a Range they cannot parse.
+pub fn parse_range(range_header: Option<&str>, file_size: u64) -> ByteRange {
+    let (start, end) = match requested_range(range_header, file_size) {
+        Some(range) => range,
+        None => return ByteRange::Whole,
+    };
+
+    if start >= file_size {
+        return ByteRange::Unsatisfiable;
+    }
+
+    let last_byte = file_size - 1;
+    let chunk_end = start.saturating_add(MAX_CHUNK_BYTES - 1);
+
+    match end {
+        Some(end) if end < start => ByteRange::Unsatisfiable,
+        Some(end) => ByteRange::Partial(start, end.min(chunk_end).min(last_byte)),
+        None => ByteRange::Partial(start, chunk_end.min(last_byte)),
+    }
+}
+
+// The first range of the header as (start, optional end), before clamping
+fn requested_range(range_header: Option<&str>, file_size: u64) -> Option<(u64, Option<u64>)> {
+    let spec = range_header?.trim().strip_prefix("bytes=")?;
+
+    // Only the first range of a multi-range request is served
+    let first = spec.split(',').next()?.trim();
+    let (start, end) = first.split_once('-')?;
+
+    match (start.trim(), end.trim()) {
+        ("", "") => None,
+        ("", suffix) => {
+            // "bytes=-500" → the last 500 bytes; "bytes=-0" asks for nothing
+            let suffix: u64 = suffix.parse().ok()?;
+            if suffix == 0 {
+                return Some((file_size, None));
+            }
+            Some((file_size.saturating_sub(suffix), None))
+        }
+        (start, "") => Some((start.parse().ok()?, None)),
+        (start, end) => Some((start.parse().ok()?, Some(end.parse().ok()?))),
+    }
+}
+
+fn read_range(media: &CachedMedia, start: u64, length: u64) -> std::io::Result<Vec<u8>> {
+    if let Some(key) = &media.key {
+        return media_crypto::read_decrypted_range(&media.path, key, start, length);
+    }
+
+    let mut file = File::open(&media.path)?;
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut buffer = Vec::with_capacity(length as usize);
+    file.take(length).read_to_end(&mut buffer)?;
+
+    Ok(buffer)
+}
+
+// ============================================================================
+// PROTOCOL HANDLER
+// ============================================================================
+
+// Accepts both lms-media://<media_id> and the Windows form
+// http://lms-media.localhost/<media_id>
+fn media_id_from_request(request: &Request<Vec<u8>>) -> Option<String> {
+    let uri = request.uri();
+
+    if let Some(segment) = uri.path().split('/').find(|s| !s.is_empty()) {
+        return Some(segment.to_string());
+    }
+
+    uri.host()
+        .filter(|host| *host != "localhost" && !host.starts_with(MEDIA_SCHEME))
+        .map(|host| host.to_string())
+}
+
+fn error_response(status: StatusCode, message: String) -> Response<Vec<u8>> {
+    log::warn!("{} request failed ({}): {}", MEDIA_SCHEME, status, message);
+
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(message.into_bytes())
+        .unwrap_or_default()
+}
+
+pub fn handle_media_request(app: &AppHandle, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let db_path = match crate::database::get_database_path(app) {
+        Ok(path) => path,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let media_id = match media_id_from_request(request) {
+        Some(id) => id,
+        None => return error_response(StatusCode::BAD_REQUEST, "Missing media id".to_string()),
+    };
+
+    let media = match resolve_cached_media(&db_path, &media_id) {
+        Ok(media) => media,
+        Err((status, e)) => return error_response(status, e),
+    };
+
+    let range_header = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok());
+
+    media_response(&media, request.method(), range_header)
+}
+
+// Plain requests (<img>, PDF viewers, downloads) get the whole file; players
+// send Range and are served one bounded chunk at a time
+fn media_response(
+    media: &CachedMedia,
+    method: &Method,
+    range_header: Option<&str>,
+) -> Response<Vec<u8>> {
+    let file_size = match media_crypto::plaintext_size(&media.path) {
+        Ok(size) => size,
+        Err(e) => {
+            return error_response(StatusCode::NOT_FOUND, format!("Media file not found: {}", e))
+        }
+    };
+
+    let mime_type = get_mime_type(&media.path, &media.media_type);
+
+    let builder = Response::builder()
+        .header(header::CONTENT_TYPE, mime_type)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+
+    if file_size == 0 {
+        return builder
+            .status(StatusCode::OK)
+            .header(header::CONTENT_LENGTH, 0)
+            .body(Vec::new())
+            .unwrap_or_default();
+    }
+
+    let (builder, start, length) = match parse_range(range_header, file_size) {
+        ByteRange::Whole => (builder.status(StatusCode::OK), 0, file_size),
+        ByteRange::Partial(start, end) => (
+            builder
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, file_size)),
+            start,
+            end - start + 1,
+        ),
+        ByteRange::Unsatisfiable => {
+            return Response::builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{}", file_size))
+                .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+                .body(Vec::new())
+                .unwrap_or_default();
+        }
+    };
+
+    let builder = builder.header(header::CONTENT_LENGTH, length);
+
+    if method == Method::HEAD {
+        return builder.body(Vec::new()).unwrap_or_default();
+    }
+
+    match read_range(media, start, length) {
+        Ok(body) => builder.body(body).unwrap_or_default(),
+        Err(e) => error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to read media {}: {}", media.media_id, e),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ByteRange::{Partial, Unsatisfiable, Whole};
+
+    #[test]
+    fn missing_or_unreadable_header_asks_for_the_whole_file() {
+        assert_eq!(parse_range(None, 100), Whole);
+        assert_eq!(parse_range(None, 10 * MAX_CHUNK_BYTES), Whole);
+        assert_eq!(parse_range(Some("items=0-10"), 100), Whole);
+        assert_eq!(parse_range(Some("bytes=abc-"), 100), Whole);
+        assert_eq!(parse_range(Some("bytes=-"), 100), Whole);
+    }
+
+    #[test]
+    fn explicit_ranges_are_clamped() {
+        assert_eq!(parse_range(Some("bytes=10-19"), 100), Partial(10, 19));
+        assert_eq!(parse_range(Some("bytes=90-500"), 100), Partial(90, 99));
+        assert_eq!(parse_range(Some("bytes=10-19, 30-39"), 100), Partial(10, 19));
+        assert_eq!(
+            parse_range(Some("bytes=0-"), 10_000_000_000),
+            Partial(0, MAX_CHUNK_BYTES - 1)
+        );
+        assert_eq!(
+            parse_range(Some("bytes=0-9999999999"), 10_000_000_000),
+            Partial(0, MAX_CHUNK_BYTES - 1)
+        );
+    }
+
+    #[test]
+    fn suffix_ranges_count_from_the_end() {
+        assert_eq!(parse_range(Some("bytes=-10"), 100), Partial(90, 99));
+        assert_eq!(parse_range(Some("bytes=-500"), 100), Partial(0, 99));
+        assert_eq!(parse_range(Some("bytes=-0"), 100), Unsatisfiable);
+    }
+
+    #[test]
+    fn unsatisfiable_ranges_are_rejected() {
+        assert_eq!(parse_range(Some("bytes=100-"), 100), Unsatisfiable);
+        assert_eq!(parse_range(Some("bytes=100-200"), 100), Unsatisfiable);
+        assert_eq!(parse_range(Some("bytes=20-10"), 100), Unsatisfiable);
+        assert_eq!(parse_range(Some("bytes=0-"), 0), Unsatisfiable);
+    }
+
+    #[test]
+    fn huge_start_does_not_overflow() {
+        let header = format!("bytes={}-", u64::MAX);
+        assert_eq!(parse_range(Some(&header), 100), Unsatisfiable);
+        assert_eq!(parse_range(Some(&header), u64::MAX), Unsatisfiable);
+
+        let header = format!("bytes={}-", u64::MAX - 1);
+        assert_eq!(parse_range(Some(&header), u64::MAX), Partial(u64::MAX - 1, u64::MAX - 1));
+    }
+
+    // An unencrypted document a little over one chunk long
+    fn large_document(name: &str) -> (CachedMedia, Vec<u8>) {
+        let contents: Vec<u8> = (0..MAX_CHUNK_BYTES + 1000).map(|i| (i % 251) as u8).collect();
+        let path = std::env::temp_dir().join(format!("{}_{}.pdf", name, std::process::id()));
+        std::fs::write(&path, &contents).unwrap();
+
+        let media = CachedMedia {
+            media_id: "m1".to_string(),
+            media_type: "document".to_string(),
+            path,
+            key: None,
+        };
+        (media, contents)
+    }
+
+    fn header_value(response: &Response<Vec<u8>>, name: header::HeaderName) -> Option<&str> {
+        response.headers().get(name).and_then(|v| v.to_str().ok())
+    }
+
+    #[test]
+    fn plain_requests_get_the_whole_file() {
+        let (media, contents) = large_document("media_whole");
+        let response = media_response(&media, &Method::GET, None);
+        let _ = std::fs::remove_file(&media.path);
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header_value(&response, header::CONTENT_RANGE), None);
+        assert_eq!(
+            header_value(&response, header::CONTENT_LENGTH),
+            Some(contents.len().to_string().as_str())
+        );
+        assert_eq!(response.body(), &contents);
+    }
+
+    #[test]
+    fn ranged_requests_get_one_chunk() {
+        let (media, contents) = large_document("media_ranged");
+        let first = media_response(&media, &Method::GET, Some("bytes=0-"));
+        let rest = media_response(&media, &Method::GET, Some("bytes=4194304-"));
+        let beyond = media_response(&media, &Method::GET, Some("bytes=99999999-"));
+        let _ = std::fs::remove_file(&media.path);
+
+        assert_eq!(first.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(first.body().len() as u64, MAX_CHUNK_BYTES);
+        assert_eq!(
+            header_value(&first, header::CONTENT_RANGE),
+            Some(format!("bytes 0-{}/{}", MAX_CHUNK_BYTES - 1, contents.len()).as_str())
+        );
+
+        assert_eq!(rest.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(rest.body(), &contents[MAX_CHUNK_BYTES as usize..]);
+
+        assert_eq!(beyond.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            header_value(&beyond, header::CONTENT_RANGE),
+            Some(format!("bytes */{}", contents.len()).as_str())
+        );
+    }
+}
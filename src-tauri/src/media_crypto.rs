@@ -0,0 +1,398 @@
+use chacha20::cipher::{KeyIvInit, StreamCipher, StreamCipherSeek};
+use chacha20::ChaCha20;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::ChaCha20Poly1305;
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// ============================================================================
+// MEDIA ENCRYPTION
+// ============================================================================
+// Encrypted files start with a fixed header (magic + nonce) followed by the
+// ChaCha20 ciphertext. ChaCha20 is a seekable stream cipher, so any byte range
+// can be decrypted without reading the file from the start.
+
+const MAGIC: &[u8; 8] = b"OAMEDIA1";
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+pub const HEADER_LEN: u64 = (MAGIC.len() + NONCE_LEN) as u64;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+pub type MediaKey = [u8; KEY_LEN];
+
+// ============================================================================
+// KEY WRAPPING
+// ============================================================================
+// Session keys never reach the database in the clear. Each one is sealed with
+// a device key kept in the OS keystore (Keychain, Credential Manager, Secret
+// Service), so a copied database cannot decrypt downloaded media on its own.
+// Sealed keys are stored as "v1:<nonce hex>:<ciphertext hex>"; sessions that
+// share a key store the same sealed value.
+
+const KEYSTORE_SERVICE: &str = "lms-offline-media";
+const KEYSTORE_ACCOUNT: &str = "device-key";
+const SEALED_PREFIX: &str = "v1:";
+const SEAL_NONCE_LEN: usize = 12;
+
+static DEVICE_KEY: Mutex<Option<MediaKey>> = Mutex::new(None);
+
+fn decode_key(hex_key: &str) -> Result<MediaKey, String> {
+    let bytes = hex::decode(hex_key).map_err(|e| format!("Corrupt media key: {}", e))?;
+
+    bytes
+        .try_into()
+        .map_err(|_| "Corrupt media key: wrong length".to_string())
+}
+
+// Loads the device key from the keystore, creating it on first use
+fn device_key() -> Result<MediaKey, String> {
+    let mut cached = DEVICE_KEY
+        .lock()
+        .map_err(|_| "Device key store is unavailable".to_string())?;
+
+    if let Some(key) = *cached {
+        return Ok(key);
+    }
+
+    let entry = keyring::Entry::new(KEYSTORE_SERVICE, KEYSTORE_ACCOUNT)
+        .map_err(|e| format!("Secure key storage is unavailable: {}", e))?;
+
+    let key = match entry.get_password() {
+        Ok(hex_key) => decode_key(&hex_key)?,
+        Err(keyring::Error::NoEntry) => {
+            let mut key = [0u8; KEY_LEN];
+            getrandom::getrandom(&mut key)
+                .map_err(|e| format!("Failed to generate device key: {}", e))?;
+            entry
+                .set_password(&hex::encode(key))
+                .map_err(|e| format!("Failed to store device key: {}", e))?;
+            key
+        }
+        Err(e) => return Err(format!("Secure key storage is unavailable: {}", e)),
+    };
+
+    *cached = Some(key);
+    Ok(key)
+}
+
+fn seal_key(key: &MediaKey) -> Result<String, String> {
+    let mut nonce = [0u8; SEAL_NONCE_LEN];
+    getrandom::getrandom(&mut nonce).map_err(|e| format!("Failed to generate nonce: {}", e))?;
+
+    let sealed = ChaCha20Poly1305::new(&device_key()?.into())
+        .encrypt(&nonce.into(), key.as_slice())
+        .map_err(|_| "Failed to seal media key".to_string())?;
+
+    Ok(format!("{}{}:{}", SEALED_PREFIX, hex::encode(nonce), hex::encode(sealed)))
+}
+
+fn open_key(stored: &str) -> Result<MediaKey, String> {
+    let (nonce, sealed) = stored
+        .strip_prefix(SEALED_PREFIX)
+        .and_then(|rest| rest.split_once(':'))
+        .ok_or_else(|| "Media key is not sealed".to_string())?;
+
+    let nonce: [u8; SEAL_NONCE_LEN] = hex::decode(nonce)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| "Corrupt media key: bad nonce".to_string())?;
+    let sealed = hex::decode(sealed).map_err(|e| format!("Corrupt media key: {}", e))?;
+
+    let key = ChaCha20Poly1305::new(&device_key()?.into())
+        .decrypt(&nonce.into(), sealed.as_slice())
+        .map_err(|_| "Media key was sealed on another device or has been tampered with".to_string())?;
+
+    key.try_into()
+        .map_err(|_| "Corrupt media key: wrong length".to_string())
+}
+
+// Seals keys stored in the clear by earlier versions. Rows that shared a key
+// keep sharing one sealed value. Returns the number of rows sealed.
+pub fn seal_legacy_session_keys(conn: &Connection) -> Result<usize, String> {
+    let legacy: Vec<(String, String)> = conn
+        .prepare(
+            "SELECT session_id, media_key FROM offline_session_keys
+             WHERE media_key NOT LIKE 'v1:%'",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect()
+        })
+        .map_err(|e| format!("Failed to load media keys: {}", e))?;
+
+    let mut sealed_keys: HashMap<String, String> = HashMap::new();
+    for (session_id, hex_key) in &legacy {
+        let sealed = match sealed_keys.get(hex_key) {
+            Some(sealed) => sealed.clone(),
+            None => {
+                let sealed = seal_key(&decode_key(hex_key)?)?;
+                sealed_keys.insert(hex_key.clone(), sealed.clone());
+                sealed
+            }
+        };
+
+        conn.execute(
+            "UPDATE offline_session_keys SET media_key = ?1 WHERE session_id = ?2",
+            params![sealed, session_id],
+        )
+        .map_err(|e| format!("Failed to seal media key: {}", e))?;
+    }
+
+    Ok(legacy.len())
+}
+
+// ============================================================================
+// SESSION KEYS
+// ============================================================================
+
+// Returns the session's key, generating one the first time it is needed
+pub fn ensure_session_key(conn: &Connection, session_id: &str) -> Result<MediaKey, String> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT media_key FROM offline_session_keys WHERE session_id = ?1",
+            params![session_id],
+            |row| row.get(0),
+        )
+        .ok();
+
+    if let Some(sealed) = existing {
+        return open_key(&sealed);
+    }
+
+    let mut key = [0u8; KEY_LEN];
+    getrandom::getrandom(&mut key).map_err(|e| format!("Failed to generate media key: {}", e))?;
+
+    conn.execute(
+        "INSERT INTO offline_session_keys (session_id, media_key, created_at)
+         VALUES (?1, ?2, ?3)",
+        params![session_id, seal_key(&key)?, chrono::Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| format!("Failed to save media key: {}", e))?;
+
+    Ok(key)
+}
+
+// Callers check the session's validity first (see commands::access)
+pub fn load_session_key(conn: &Connection, session_id: &str) -> Result<MediaKey, String> {
+    let sealed: String = conn
+        .query_row(
+            "SELECT media_key FROM offline_session_keys WHERE session_id = ?1",
+            params![session_id],
+            |row| row.get(0),
+        )
+        .map_err(|_| format!("Media key for offline session {} has been revoked", session_id))?;
+
+    open_key(&sealed)
+}
+
+// Profiles on a shared device reuse a course's downloaded media. A session
+// saved for a course whose files are already encrypted takes over their key,
+// unless its own key already protects files of its own.
+pub fn share_course_key(conn: &Connection, session_id: &str, course_id: &str) -> Result<(), String> {
+    let owns_files: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM media_cache WHERE encryption_session_id = ?1)",
+            params![session_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to check media key: {}", e))?;
+
+    if owns_files {
+        return Ok(());
+    }
+
+    let course_key: Option<String> = conn
+        .query_row(
+            "SELECT k.media_key FROM media_cache mc
+             JOIN offline_session_keys k ON k.session_id = mc.encryption_session_id
+             WHERE mc.course_id = ?1 AND mc.encryption_session_id != ?2
+             ORDER BY mc.downloaded_at DESC
+             LIMIT 1",
+            params![course_id, session_id],
+            |row| row.get(0),
+        )
+        .ok();
+
+    if let Some(sealed) = course_key {
+        conn.execute(
+            "INSERT INTO offline_session_keys (session_id, media_key, created_at)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(session_id) DO UPDATE SET media_key = excluded.media_key",
+            params![session_id, sealed, chrono::Utc::now().to_rfc3339()],
+        )
+        .map_err(|e| format!("Failed to share media key: {}", e))?;
+    }
+
+    Ok(())
+}
+
+// Moves files encrypted under a session to another live session holding the
+// same key, so revoking one profile's key leaves the others' playback intact
+fn hand_over_media(conn: &Connection, session_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE media_cache
+         SET encryption_session_id = (
+             SELECT other.session_id FROM offline_session_keys own
+             JOIN offline_session_keys other
+               ON other.media_key = own.media_key AND other.session_id != own.session_id
+             JOIN offline_sessions s ON s.id = other.session_id AND s.is_deleted = 0
+             WHERE own.session_id = ?1
+             ORDER BY s.expires_at DESC
+             LIMIT 1
+         )
+         WHERE encryption_session_id = ?1
+           AND EXISTS (
+             SELECT 1 FROM offline_session_keys own
+             JOIN offline_session_keys other
+               ON other.media_key = own.media_key AND other.session_id != own.session_id
+             JOIN offline_sessions s ON s.id = other.session_id AND s.is_deleted = 0
+             WHERE own.session_id = ?1
+           )",
+        params![session_id],
+    )
+    .map_err(|e| format!("Failed to hand over media: {}", e))?;
+
+    Ok(())
+}
+
+pub fn revoke_session_key(conn: &Connection, session_id: &str) -> Result<(), String> {
+    hand_over_media(conn, session_id)?;
+
+    conn.execute(
+        "DELETE FROM offline_session_keys WHERE session_id = ?1",
+        params![session_id],
+    )
+    .map_err(|e| format!("Failed to revoke media key: {}", e))?;
+
+    Ok(())
+}
+
+// Drops keys whose session has been soft-deleted or no longer exists
+pub fn revoke_orphaned_session_keys(conn: &Connection) -> Result<usize, String> {
+    let orphaned: Vec<String> = conn
+        .prepare(
+            "SELECT session_id FROM offline_session_keys
+             WHERE session_id NOT IN (SELECT id FROM offline_sessions WHERE is_deleted = 0)",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get(0))?
+                .collect()
+        })
+        .map_err(|e| format!("Failed to load media keys: {}", e))?;
+
+    for session_id in &orphaned {
+        hand_over_media(conn, session_id)?;
+    }
+
+    conn.execute(
+        "DELETE FROM offline_session_keys
+         WHERE session_id NOT IN (SELECT id FROM offline_sessions WHERE is_deleted = 0)",
+        [],
+    )
+    .map_err(|e| format!("Failed to revoke media keys: {}", e))
+}
+
+// ============================================================================
+// FILE ENCRYPTION
+// ============================================================================
+
+pub fn is_encrypted(path: &Path) -> std::io::Result<bool> {
+    let mut magic = [0u8; MAGIC.len()];
+    let mut file = File::open(path)?;
+
+    match file.read_exact(&mut magic) {
+        Ok(()) => Ok(&magic == MAGIC),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+// Size of the original media, excluding the encryption header
+pub fn plaintext_size(path: &Path) -> std::io::Result<u64> {
+    let size = fs::metadata(path)?.len();
+
+    if is_encrypted(path)? {
+        Ok(size.saturating_sub(HEADER_LEN))
+    } else {
+        Ok(size)
+    }
+}
+
+// Encrypts a downloaded file in place. Files that are already encrypted are
+// left untouched, so saving the same cache entry twice is harmless.
+pub fn encrypt_file_in_place(path: &Path, key: &MediaKey) -> Result<(), String> {
+    if is_encrypted(path).map_err(|e| format!("Failed to read media file: {}", e))? {
+        return Ok(());
+    }
+
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut nonce).map_err(|e| format!("Failed to generate nonce: {}", e))?;
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".encrypting");
+    let tmp_path = PathBuf::from(tmp_path);
+    let result = (|| -> std::io::Result<()> {
+        let mut input = File::open(path)?;
+        let mut output = File::create(&tmp_path)?;
+
+        output.write_all(MAGIC)?;
+        output.write_all(&nonce)?;
+
+        let mut cipher = ChaCha20::new(key.into(), &nonce.into());
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+
+        loop {
+            let read = input.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            cipher.apply_keystream(&mut buffer[..read]);
+            output.write_all(&buffer[..read])?;
+        }
+
+        output.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+
+    result.map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        format!("Failed to encrypt media file: {}", e)
+    })
+}
+
+// Reads `length` plaintext bytes starting at `start` from an encrypted file
+pub fn read_decrypted_range(
+    path: &Path,
+    key: &MediaKey,
+    start: u64,
+    length: u64,
+) -> std::io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+
+    let mut header = [0u8; HEADER_LEN as usize];
+    file.read_exact(&mut header)?;
+    if &header[..MAGIC.len()] != MAGIC {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Media file is not encrypted",
+        ));
+    }
+
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce.copy_from_slice(&header[MAGIC.len()..]);
+
+    file.seek(SeekFrom::Start(HEADER_LEN + start))?;
+    let mut buffer = Vec::with_capacity(length as usize);
+    file.take(length).read_to_end(&mut buffer)?;
+
+    let mut cipher = ChaCha20::new(key.into(), &nonce.into());
+    cipher.seek(start);
+    cipher.apply_keystream(&mut buffer);
+
+    Ok(buffer)
+}
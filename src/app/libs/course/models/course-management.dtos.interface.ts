@@ -45,6 +45,8 @@ export interface CourseFull {
   category: string;
   level: 'BEGINNER' | 'INTERMEDIATE' | 'ADVANCED';
   duration: number;
+  // How modules unlock; saved locally with the course
+  access_policy?: 'free' | 'sequential' | 'quiz_gated';
   created_at: string;
   updated_at: string;
 }
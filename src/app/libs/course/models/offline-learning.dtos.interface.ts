@@ -21,6 +21,7 @@ export interface OfflineSessionBasic {
   expires_at: string;
   package_version: string;
   presigned_url_expiry_days: number;
+  grace_period_hours?: number;
   last_synced_at?: string;
   sync_count: number;
   is_expired: boolean;
@@ -82,6 +83,7 @@ export interface DownloadCourseForOfflineResponse {
   package_version: string;
   course_package: OfflineCoursePackage;
   download_expires_at: string;
+  offline_grace_period_hours?: number;
   estimated_download_size_mb: number;
 }
 
@@ -128,6 +130,7 @@ export interface SyncOfflineProgressRequest {
     final_exam_attempt?: QuizAttemptOffline;
     last_accessed_module_id?: string;
     total_time_spent_seconds?: number;
+    provisional_certificate_code?: string;
   };
 }
 
@@ -165,8 +165,14 @@ export class CourseProvider {
 
   checkEnrollmentEligibility(courseId: string): Observable<CheckEnrollmentEligibilityResponse> {
     if (this.connectivity.isOffline()) {
-      this.toasts.warning('You need to be online to check enrollment eligibility.');
-      return throwError(() => new Error('Checking enrollment eligibility requires an internet connection.'));
+      return from(
+        this.db.getCurrentUser().then(user => this.db.checkCourseEligibility(courseId, user.id))
+      ).pipe(
+        catchError(error => {
+          this.toasts.error('Unable to check enrollment eligibility offline.');
+          return throwError(() => error);
+        })
+      );
     }
 
     return this.http.get<CheckEnrollmentEligibilityResponse>(
@@ -940,12 +946,13 @@ export class CourseProvider {
       this.db.getCurrentUser().then(async user => {
         const attempt = await this.db.getQuizAttemptById(attemptId);
         const quiz = await this.db.getQuizById(attempt.quiz_id);
-        const questions = await this.db.getQuizQuestions(attempt.quiz_id);
-        const answers = await this.db.getAttemptAnswers(attemptId);
+        const questions = await this.db.getAttemptQuestions(attemptId);
 
         const submittedAnswers: { [question_id: string]: string } = {};
-        answers.forEach((answer: any) => {
-          submittedAnswers[answer.question_id] = answer.selected_option_id;
+        questions.forEach((question: any) => {
+          if (question.selected_option_id) {
+            submittedAnswers[question.id] = question.selected_option_id;
+          }
         });
 
         return {
@@ -1005,21 +1012,12 @@ export class CourseProvider {
       this.db.getCurrentUser().then(async user => {
         const quiz = await this.db.getQuizById(quizId);
         const questions = await this.db.getQuizQuestions(quizId);
-        const existingAttempts = await this.db.getQuizAttempts(quizId, user.id);
-
-        const tempAttempt = {
-          id: `temp_attempt_${Date.now()}`,
-          student_id: user.id,
-          quiz_id: quizId,
-          attempt_number: existingAttempts.length + 1,
-          status: 'in_progress' as const,
-          started_at: new Date().toISOString(),
-          created_at: new Date().toISOString(),
-          updated_at: new Date().toISOString()
-        };
+        const started = await this.db.startQuizAttempt(quizId, user.id);
+        const tempAttempt = started.attempt;
 
-        await this.db.saveQuizAttempt(tempAttempt);
-        await this.db.addToSyncQueue('create', 'quiz_attempts', tempAttempt.id, { quiz_id: quizId });
+        if (!started.resumed) {
+          await this.db.addToSyncQueue('create', 'quiz_attempts', tempAttempt.id, { quiz_id: quizId });
+        }
 
         this.toasts.info('Quiz started offline. Your answers will sync when online.');
 
@@ -1049,17 +1047,12 @@ export class CourseProvider {
           throw new Error('Question not found');
         }
 
-        const selectedOption = question.options.find((o: any) => o.id === request.selected_option_id);
-        const isCorrect = (selectedOption?.is_correct === 1 || selectedOption?.is_correct === true);
-        const pointsEarned = isCorrect ? (question.points || 1) : 0;
-
+        // Correctness is graded in the backend and stays hidden until the attempt is completed
         const answerToSave = {
           id: `temp_answer_${Date.now()}`,
           attempt_id: attemptId,
           question_id: request.question_id,
           selected_option_id: request.selected_option_id,
-          is_correct: isCorrect ? 1 : 0,
-          points_earned: pointsEarned,
           created_at: new Date().toISOString(),
           updated_at: new Date().toISOString()
         };
@@ -1068,9 +1061,7 @@ export class CourseProvider {
         await this.db.addToSyncQueue('create', 'quiz_answers', answerToSave.id, {
           attempt_id: attemptId,
           question_id: request.question_id,
-          selected_option_id: request.selected_option_id,
-          is_correct: isCorrect,
-          points_earned: pointsEarned
+          selected_option_id: request.selected_option_id
         });
 
         const allQuestions = await this.db.getQuizQuestions(attempt.quiz_id);
@@ -1083,8 +1074,8 @@ export class CourseProvider {
             attempt_id: answerToSave.attempt_id,
             question_id: answerToSave.question_id,
             selected_option_id: answerToSave.selected_option_id,
-            is_correct: isCorrect,
-            points_earned: answerToSave.points_earned,
+            is_correct: false,
+            points_earned: 0,
             created_at: answerToSave.created_at,
             updated_at: answerToSave.updated_at
           },
@@ -1105,12 +1096,14 @@ export class CourseProvider {
       this.db.getCurrentUser().then(async user => {
         const attempt = await this.db.getQuizAttemptById(attemptId);
         const quiz = await this.db.getQuizById(attempt.quiz_id);
+
+        // The backend grades the attempt when it is submitted; the score is only readable afterwards
+        await this.db.updateQuizAttemptStatus(attemptId, 'completed');
         const scoreData = await this.db.calculateAttemptScore(attemptId);
 
         const score = scoreData.percentage || 0;
-        const passed = score >= quiz.pass_mark_percentage;
+        const passed = scoreData.passed === true;
 
-        await this.db.updateQuizAttemptStatus(attemptId, 'completed', score, passed);
         await this.db.addToSyncQueue('update', 'quiz_attempts', attemptId, {
           status: 'completed',
           score: score,
@@ -1190,34 +1183,27 @@ export class CourseProvider {
       this.db.getCurrentUser().then(async user => {
         const attempt = await this.db.getQuizAttemptById(attemptId);
         const quiz = await this.db.getQuizById(attempt.quiz_id);
-        const questions = await this.db.getQuizQuestions(attempt.quiz_id);
-        const answers = await this.db.getAttemptAnswers(attemptId);
-
-        const questionsWithAnswers = questions.map((question: any) => {
-          const answer = answers.find((a: any) => a.question_id === question.id);
-          const correctOption = question.options.find((o: any) => o.is_correct === 1 || o.is_correct === true);
-          const isAnswerCorrect = answer?.is_correct === 1 || answer?.is_correct === true;
-
-          return {
-            id: question.id,
-            quiz_id: question.quiz_id,
-            question_text: question.question_text,
-            image_url: question.image_url,
-            order: question.order,
-            points: question.points,
-            options: question.options.map((opt: any) => ({
-              id: opt.id,
-              question_id: opt.question_id || question.id,
-              option_text: opt.option_text,
-              is_correct: opt.is_correct === 1 || opt.is_correct === true,
-              order: opt.order
-            })),
-            student_selected_option_id: answer?.selected_option_id || undefined,
-            correct_option_id: correctOption?.id || '',
-            is_correct: isAnswerCorrect,
-            points_earned: answer?.points_earned || 0
-          };
-        });
+        const review = await this.db.getAttemptReview(attemptId);
+
+        const questionsWithAnswers = review.questions.map((question: any) => ({
+          id: question.id,
+          quiz_id: question.quiz_id,
+          question_text: question.question_text,
+          image_url: question.image_url,
+          order: question.order,
+          points: question.points,
+          options: question.options.map((opt: any) => ({
+            id: opt.id,
+            question_id: opt.question_id,
+            option_text: opt.option_text,
+            is_correct: opt.is_correct === true,
+            order: opt.order
+          })),
+          student_selected_option_id: question.selected_option_id || undefined,
+          correct_option_id: question.correct_option_id || '',
+          is_correct: question.is_correct === true,
+          points_earned: question.points_earned || 0
+        }));
 
         let score = attempt.score;
         let passed = attempt.passed;
@@ -352,6 +352,9 @@ export class OfflineDownloadService {
       });
       console.log('✅ Course saved to DB');
 
+      await this.tauriDb.saveCoursePrerequisites(course.id, response.course_package.prerequisites || []);
+      console.log(`✅ Saved ${response.course_package.prerequisites?.length || 0} prerequisites`);
+
       // ============================================================================
       // STEP 2.5: SAVE ENROLLMENT (NEW)
       // ============================================================================
@@ -581,6 +584,7 @@ export class OfflineDownloadService {
         expires_at: response.download_expires_at,
         package_version: response.package_version,
         presigned_url_expiry_days: 7,
+        grace_period_hours: response.offline_grace_period_hours ?? null,
         last_synced_at: null,
         sync_count: 0,
         is_deleted: false,
@@ -599,6 +603,15 @@ export class OfflineDownloadService {
       await this.tauriDb.saveOfflineSession(sessionData);
       console.log('✅ Offline session record saved');
 
+      // ============================================================================
+      // STEP 6: REBUILD PROGRESS
+      // ============================================================================
+      // The saves above only refresh counters; modules and the course are
+      // completed once the whole package is in
+      console.log('\n🔁 Step 6: Rebuilding progress...');
+      const recompute = await this.tauriDb.recomputeProgress();
+      console.log(`✅ Progress rebuilt: ${recompute.modules_changed} module rows changed`);
+
       // ============================================================================
       // FINAL SUMMARY
       // ============================================================================
@@ -638,12 +651,20 @@ export class OfflineDownloadService {
     try {
       console.log(`🔄 Syncing progress for course ${courseId}...`);
 
+      // Certificates issued offline are confirmed by the server from their code
+      const provisionalCertificate = await this.tauriDb.getProvisionalCertificate(courseId);
+      const pendingCode = provisionalCertificate?.is_provisional
+        ? provisionalCertificate.verification_code
+        : undefined;
+
       const request: SyncOfflineProgressRequest = {
         course_id: courseId,
         offline_session_id: sessionId,
         downloaded_at: new Date().toISOString(),
         synced_at: new Date().toISOString(),
-        progress_data: progressData
+        progress_data: pendingCode
+          ? { ...progressData, provisional_certificate_code: pendingCode }
+          : progressData
       };
 
       const httpResponse = await this.http.post<SyncOfflineProgressResponse>(
@@ -657,6 +678,14 @@ export class OfflineDownloadService {
       }
 
       await this.tauriDb.updateOfflineSessionSyncInfo(sessionId);
+
+      if (pendingCode && response.certificates_issued?.length) {
+        await this.tauriDb.replaceProvisionalCertificate(pendingCode, {
+          id: response.certificates_issued[0]
+        });
+        console.log('📜 Provisional certificate replaced by the official one');
+      }
+
       console.log('✅ Progress synced successfully');
       return response;
 
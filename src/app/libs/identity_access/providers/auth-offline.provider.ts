@@ -38,7 +38,8 @@ export class AuthOfflineProvider {
   /**
    * Login (OFFLINE: Check if user exists in local database)
    * ✅ UPDATED: Matches DataStrategy signature - login(email, password)
-   * Password is ignored offline for security
+   * Password unlocks the cached profile; it is checked against the
+   * credential saved at the last online login
    */
   login(email: string, password?: string): Observable<LoginResponse> {
     return from(
@@ -51,7 +52,12 @@ export class AuthOfflineProvider {
             throw new Error('No account found for this email. Please connect to the internet to log in.');
           }
 
-          // 2. Get stored tokens (if any)
+          if (!password) {
+            throw new Error('Password is required to sign in offline.');
+          }
+
+          // 2. Unlock the profile with its password, then get its stored tokens (if any)
+          await this.tauriDb.switchProfile(user.id, password);
           const tokens = await this.tauriDb.getAuthTokens();
 
           console.log('✅ Offline login successful - user found in local database');
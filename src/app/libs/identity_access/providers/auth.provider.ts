@@ -123,7 +123,7 @@ export class AuthProvider {
 
   login(request: LoginRequest): Observable<LoginResponse> {
     if (this.connectivity.isOffline()) {
-      return this.loginOffline(request.email);
+      return this.loginOffline(request.email, request.password);
     }
 
     return this.http.post<LoginResponse>(
@@ -134,6 +134,7 @@ export class AuthProvider {
       tap(async response => {
         // Save auth data locally for offline access
         await this.saveAuthDataLocally(response);
+        await this.saveOfflineCredential(request.password);
       })
     );
   }
@@ -229,7 +230,7 @@ export class AuthProvider {
   // OFFLINE IMPLEMENTATIONS
   // ============================================================================
 
-  private loginOffline(email: string): Observable<LoginResponse> {
+  private loginOffline(email: string, password: string): Observable<LoginResponse> {
     return from(
       (async () => {
         try {
@@ -242,7 +243,8 @@ export class AuthProvider {
             );
           }
 
-          // 2. Get stored tokens (if any)
+          // 2. Unlock the profile with its password, then get its stored tokens (if any)
+          await this.db.switchProfile(user.id, password);
           const tokens = await this.db.getAuthTokens();
 
           console.log('✅ Offline login successful - user found in local database');
@@ -313,7 +315,8 @@ export class AuthProvider {
           authResponse.access_token.token,
           authResponse.access_token.expires_at,
           authResponse.refresh_token.token,
-          authResponse.refresh_token.expires_at
+          authResponse.refresh_token.expires_at,
+          authResponse.user?.id
         );
         console.log('✅ Auth tokens saved to local database');
       }
@@ -327,6 +330,19 @@ export class AuthProvider {
     }
   }
 
+  /**
+   * Remember the password the server accepted so this profile can be
+   * unlocked offline
+   */
+  private async saveOfflineCredential(password: string): Promise<void> {
+    try {
+      await this.db.saveOfflineCredential(password);
+      console.log('✅ Offline credential saved to local database');
+    } catch (error) {
+      console.error('❌ Failed to save offline credential:', error);
+    }
+  }
+
   /**
    * Save user data to local database
    */
@@ -44,7 +44,8 @@ export class TauriDatabaseService {
     accessToken: string,
     accessExpiresAt: string,
     refreshToken: string,
-    refreshExpiresAt: string
+    refreshExpiresAt: string,
+    userId?: string
   ): Promise<string> {
     const dbPath = await this.ensurePath();
     return invoke<string>('save_auth_tokens', {
@@ -52,7 +53,8 @@ export class TauriDatabaseService {
       accessToken,
       accessExpiresAt,
       refreshToken,
-      refreshExpiresAt
+      refreshExpiresAt,
+      userId: userId ?? null
     });
   }
 
@@ -70,6 +72,37 @@ export class TauriDatabaseService {
     return invoke<string>('clear_auth_tokens', { dbPath });
   }
 
+  async saveOfflineCredential(password: string): Promise<string> {
+    const dbPath = await this.ensurePath();
+    return invoke<string>('save_offline_credential', { dbPath, password });
+  }
+
+  // ============================================================================
+  // PROFILE COMMANDS
+  // ============================================================================
+
+  async listProfiles(): Promise<any[]> {
+    const dbPath = await this.ensurePath();
+    const profilesJson = await invoke<string>('list_profiles', { dbPath });
+    return JSON.parse(profilesJson);
+  }
+
+  async switchProfile(userId: string, password: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const profileJson = await invoke<string>('switch_profile', { dbPath, userId, password });
+    return JSON.parse(profileJson);
+  }
+
+  async removeProfile(
+    userId: string,
+    password: string | null = null,
+    discardUnsynced: boolean = false
+  ): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const reportJson = await invoke<string>('remove_profile', { dbPath, userId, password, discardUnsynced });
+    return JSON.parse(reportJson);
+  }
+
   async checkTokenExpired(expiresAt: string): Promise<boolean> {
     return invoke<boolean>('check_token_expired', { expiresAt });
   }
@@ -114,6 +147,26 @@ export class TauriDatabaseService {
     return JSON.parse(coursesJson);
   }
 
+  async queryCourses(query: {
+    category?: string | string[];
+    level?: string | string[];
+    is_published?: boolean;
+    min_duration?: number;
+    max_duration?: number;
+    enrolled?: boolean;
+    downloaded?: boolean;
+    sort?: 'newest' | 'title' | 'popular';
+    cursor?: string | null;
+    limit?: number;
+  } = {}): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const pageJson = await invoke<string>('query_courses', {
+      dbPath,
+      queryData: JSON.stringify(query)
+    });
+    return JSON.parse(pageJson);
+  }
+
   async getEnrolledCourses(studentId: string): Promise<any[]> {
     const dbPath = await this.ensurePath();
     const coursesJson = await invoke<string>('get_enrolled_courses', {
@@ -158,6 +211,301 @@ export class TauriDatabaseService {
     });
   }
 
+  async saveCoursePrerequisites(courseId: string, prerequisites: any[]): Promise<string> {
+    const dbPath = await this.ensurePath();
+    return invoke<string>('save_course_prerequisites', {
+      dbPath,
+      courseId,
+      prerequisitesData: JSON.stringify(prerequisites)
+    });
+  }
+
+  async checkCourseEligibility(courseId: string, studentId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const eligibilityJson = await invoke<string>('check_course_eligibility', {
+      dbPath,
+      courseId,
+      studentId
+    });
+    return JSON.parse(eligibilityJson);
+  }
+
+  async evaluateCourseCompletion(enrollmentId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const completionJson = await invoke<string>('evaluate_course_completion', {
+      dbPath,
+      enrollmentId
+    });
+    return JSON.parse(completionJson);
+  }
+
+  // Rebuilds module counters and statuses; omit the id to check every enrollment
+  async recomputeProgress(enrollmentId?: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const reportJson = await invoke<string>('recompute_progress', {
+      dbPath,
+      enrollmentId: enrollmentId ?? null
+    });
+    return JSON.parse(reportJson);
+  }
+
+  async saveResumePosition(enrollmentId: string, contentId: string, position: any): Promise<void> {
+    const dbPath = await this.ensurePath();
+    await invoke('save_resume_position', {
+      dbPath,
+      enrollmentId,
+      contentId,
+      positionData: JSON.stringify(position)
+    });
+  }
+
+  async getResumePosition(enrollmentId: string, contentId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const positionJson = await invoke<string>('get_resume_position', {
+      dbPath,
+      enrollmentId,
+      contentId
+    });
+    return JSON.parse(positionJson);
+  }
+
+  async getContinueLearning(enrollmentId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const continueJson = await invoke<string>('get_continue_learning', { dbPath, enrollmentId });
+    return JSON.parse(continueJson);
+  }
+
+  async getActivityCalendar(studentId: string, fromDate: string, toDate: string): Promise<any[]> {
+    const dbPath = await this.ensurePath();
+    const calendarJson = await invoke<string>('get_activity_calendar', {
+      dbPath,
+      studentId,
+      fromDate,
+      toDate,
+      utcOffsetMinutes: -new Date().getTimezoneOffset()
+    });
+    return JSON.parse(calendarJson);
+  }
+
+  async getLearningStreak(studentId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const streakJson = await invoke<string>('get_learning_streak', {
+      dbPath,
+      studentId,
+      utcOffsetMinutes: -new Date().getTimezoneOffset()
+    });
+    return JSON.parse(streakJson);
+  }
+
+  async setWeeklyGoal(studentId: string, targetMinutes: number, targetItems?: number): Promise<void> {
+    const dbPath = await this.ensurePath();
+    await invoke('set_weekly_goal', {
+      dbPath,
+      studentId,
+      targetMinutes,
+      targetItems: targetItems ?? null
+    });
+  }
+
+  async getWeeklyGoalProgress(studentId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const progressJson = await invoke<string>('get_weekly_goal_progress', {
+      dbPath,
+      studentId,
+      utcOffsetMinutes: -new Date().getTimezoneOffset()
+    });
+    return JSON.parse(progressJson);
+  }
+
+  async search(
+    query: string,
+    scope: 'all' | 'courses' | 'modules' | 'content' | 'questions' = 'all',
+    limit?: number
+  ): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const resultsJson = await invoke<string>('search', {
+      dbPath,
+      query,
+      scope,
+      limit: limit ?? null
+    });
+    return JSON.parse(resultsJson);
+  }
+
+  async rebuildSearchIndex(): Promise<string> {
+    const dbPath = await this.ensurePath();
+    return await invoke<string>('rebuild_search_index', { dbPath });
+  }
+
+  async createBookmark(bookmark: any): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const bookmarkJson = await invoke<string>('create_bookmark', {
+      dbPath,
+      bookmarkData: JSON.stringify(bookmark)
+    });
+    return JSON.parse(bookmarkJson);
+  }
+
+  async updateBookmark(bookmarkId: string, changes: any): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const bookmarkJson = await invoke<string>('update_bookmark', {
+      dbPath,
+      bookmarkId,
+      bookmarkData: JSON.stringify(changes)
+    });
+    return JSON.parse(bookmarkJson);
+  }
+
+  async deleteBookmark(bookmarkId: string): Promise<void> {
+    const dbPath = await this.ensurePath();
+    await invoke('delete_bookmark', { dbPath, bookmarkId });
+  }
+
+  async createNote(note: any): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const noteJson = await invoke<string>('create_note', {
+      dbPath,
+      noteData: JSON.stringify(note)
+    });
+    return JSON.parse(noteJson);
+  }
+
+  async updateNote(noteId: string, changes: any): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const noteJson = await invoke<string>('update_note', {
+      dbPath,
+      noteId,
+      noteData: JSON.stringify(changes)
+    });
+    return JSON.parse(noteJson);
+  }
+
+  async deleteNote(noteId: string): Promise<void> {
+    const dbPath = await this.ensurePath();
+    await invoke('delete_note', { dbPath, noteId });
+  }
+
+  async getCourseAnnotations(courseId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const annotationsJson = await invoke<string>('get_course_annotations', { dbPath, courseId });
+    return JSON.parse(annotationsJson);
+  }
+
+  async getContentAnnotations(contentId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const annotationsJson = await invoke<string>('get_content_annotations', { dbPath, contentId });
+    return JSON.parse(annotationsJson);
+  }
+
+  async exportCourseNotes(courseId: string, format: 'markdown' | 'json' = 'markdown'): Promise<string> {
+    const dbPath = await this.ensurePath();
+    return await invoke<string>('export_course_notes', { dbPath, courseId, format });
+  }
+
+  async saveSyncedAnnotations(annotations: { bookmarks?: any[]; notes?: any[] }): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const resultJson = await invoke<string>('save_synced_annotations', {
+      dbPath,
+      annotationsData: JSON.stringify(annotations)
+    });
+    return JSON.parse(resultJson);
+  }
+
+  async getDueReviews(studentId: string, courseId?: string, limit?: number): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const reviewsJson = await invoke<string>('get_due_reviews', {
+      dbPath,
+      studentId,
+      courseId: courseId ?? null,
+      limit: limit ?? null
+    });
+    return JSON.parse(reviewsJson);
+  }
+
+  async recordReviewResult(cardId: string, result: any): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const reviewJson = await invoke<string>('record_review_result', {
+      dbPath,
+      cardId,
+      resultData: JSON.stringify(result)
+    });
+    return JSON.parse(reviewJson);
+  }
+
+  async startLearningSession(enrollmentId: string, contentId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const sessionJson = await invoke<string>('start_learning_session', {
+      dbPath,
+      enrollmentId,
+      contentId
+    });
+    return JSON.parse(sessionJson);
+  }
+
+  async heartbeatLearningSession(sessionId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const sessionJson = await invoke<string>('heartbeat_learning_session', { dbPath, sessionId });
+    return JSON.parse(sessionJson);
+  }
+
+  async stopLearningSession(sessionId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const sessionJson = await invoke<string>('stop_learning_session', { dbPath, sessionId });
+    return JSON.parse(sessionJson);
+  }
+
+  async getTimeSpent(enrollmentId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const timeJson = await invoke<string>('get_time_spent', { dbPath, enrollmentId });
+    return JSON.parse(timeJson);
+  }
+
+  async getWeeklyStudyTime(): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const weeklyJson = await invoke<string>('get_weekly_study_time', {
+      dbPath,
+      utcOffsetMinutes: -new Date().getTimezoneOffset()
+    });
+    return JSON.parse(weeklyJson);
+  }
+
+  async generateProvisionalCertificate(enrollmentId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const certificateJson = await invoke<string>('generate_provisional_certificate', {
+      dbPath,
+      enrollmentId
+    });
+    return JSON.parse(certificateJson);
+  }
+
+  async getProvisionalCertificate(courseId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const certificateJson = await invoke<string>('get_provisional_certificate', {
+      dbPath,
+      courseId
+    });
+    return JSON.parse(certificateJson);
+  }
+
+  async replaceProvisionalCertificate(verificationCode: string, certificate: any): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const certificateJson = await invoke<string>('replace_provisional_certificate', {
+      dbPath,
+      verificationCode,
+      certificateData: JSON.stringify(certificate)
+    });
+    return JSON.parse(certificateJson);
+  }
+
+  async getModuleAccessMap(enrollmentId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const accessMapJson = await invoke<string>('get_module_access_map', {
+      dbPath,
+      enrollmentId
+    });
+    return JSON.parse(accessMapJson);
+  }
+
   async getCourseFinalExam(courseId: string): Promise<any> {
     const dbPath = await this.ensurePath();
     const examJson = await invoke<string>('get_course_final_exam', {
@@ -345,6 +693,34 @@ export class TauriDatabaseService {
     });
   }
 
+  async startQuizAttempt(quizId: string, studentId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const resultJson = await invoke<string>('start_quiz_attempt', {
+      dbPath,
+      quizId,
+      studentId
+    });
+    return JSON.parse(resultJson);
+  }
+
+  async getAttemptQuestions(attemptId: string): Promise<any[]> {
+    const dbPath = await this.ensurePath();
+    const questionsJson = await invoke<string>('get_attempt_questions', {
+      dbPath,
+      attemptId
+    });
+    return JSON.parse(questionsJson);
+  }
+
+  async getAttemptReview(attemptId: string): Promise<any> {
+    const dbPath = await this.ensurePath();
+    const reviewJson = await invoke<string>('get_attempt_review', {
+      dbPath,
+      attemptId
+    });
+    return JSON.parse(reviewJson);
+  }
+
   async getQuizAttempts(quizId: string, studentId: string): Promise<any[]> {
     const dbPath = await this.ensurePath();
     const attemptsJson = await invoke<string>('get_quiz_attempts', {